@@ -1,6 +1,9 @@
 use blueprint_sdk::build;
 use blueprint_sdk::tangle::blueprint;
-use hyperlane_relayer_blueprint_lib::set_config;
+use hyperlane_relayer_blueprint_lib::{
+    effective_config, relayer_status, rollback_config, rotate_signer, set_config,
+    set_config_inline, set_rpc_urls, signer_balances, stop_relayer,
+};
 use std::path::Path;
 use std::process;
 
@@ -21,7 +24,17 @@ fn main() {
         name: "experiment",
         master_manager_revision: "Latest",
         manager: { Evm = "HyperlaneRelayerBlueprint" },
-        jobs: [set_config]
+        jobs: [
+            set_config,
+            stop_relayer,
+            relayer_status,
+            rollback_config,
+            signer_balances,
+            rotate_signer,
+            set_config_inline,
+            effective_config,
+            set_rpc_urls
+        ]
     };
 
     match blueprint {
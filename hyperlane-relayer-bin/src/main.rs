@@ -44,7 +44,10 @@ async fn main() -> color_eyre::Result<()> {
     // Consumer
     let tangle_consumer = TangleConsumer::new(tangle_client.rpc_client.clone(), sr25519_signer);
 
-    let context = Arc::new(blueprint::HyperlaneContext::new(env.clone(), data_dir).await?);
+    let settings = blueprint::settings::Settings::from_env()?;
+    let context = Arc::new(
+        blueprint::HyperlaneContext::with_settings(env.clone(), data_dir, settings).await?,
+    );
 
     sdk::info!("Starting the event watcher ...");
 
@@ -55,6 +58,38 @@ async fn main() -> color_eyre::Result<()> {
                     blueprint::SET_CONFIG_JOB_ID,
                     blueprint::set_config.layer(TangleLayer),
                 )
+                .route(
+                    blueprint::STOP_RELAYER_JOB_ID,
+                    blueprint::stop_relayer.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::RELAYER_STATUS_JOB_ID,
+                    blueprint::relayer_status.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::ROLLBACK_CONFIG_JOB_ID,
+                    blueprint::rollback_config.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::SIGNER_BALANCES_JOB_ID,
+                    blueprint::signer_balances.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::ROTATE_SIGNER_JOB_ID,
+                    blueprint::rotate_signer.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::SET_CONFIG_INLINE_JOB_ID,
+                    blueprint::set_config_inline.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::EFFECTIVE_CONFIG_JOB_ID,
+                    blueprint::effective_config.layer(TangleLayer),
+                )
+                .route(
+                    blueprint::SET_RPC_URLS_JOB_ID,
+                    blueprint::set_rpc_urls.layer(TangleLayer),
+                )
                 .with_context(context.clone()),
         )
         .producer(tangle_producer)
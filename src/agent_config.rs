@@ -0,0 +1,466 @@
+//! A typed model of the Hyperlane agent config, used to validate configs before they're applied
+//!
+//! See <https://docs.hyperlane.xyz/docs/operate/config-reference>.
+
+use crate::registry::{self, DefaultChain};
+use crate::rpc_urls::RpcConsensusType;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+
+/// The agent config, after merging every config file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentConfig {
+    #[serde(default)]
+    pub chains: BTreeMap<String, ChainConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_rpc_consensus_type: Option<String>,
+    /// Any other agent settings, passed through untouched
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The config for a single chain
+///
+/// Every field is optional here, since a chain can be spread over multiple config files. The
+/// required fields are checked after merging, in [`validate`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_id: Option<u32>,
+    /// A number for EVM chains, and a string for Cosmos chains
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Protocol>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_urls: Option<Vec<RpcUrl>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mailbox: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merkle_tree_hook: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_announce: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interchain_gas_paymaster: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<IndexConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Value>,
+    /// Any other chain settings, passed through untouched
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The VM family of a chain
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Ethereum,
+    Sealevel,
+    Cosmos,
+    CosmosNative,
+    Fuel,
+    Starknet,
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Protocol::Ethereum => "ethereum",
+            Protocol::Sealevel => "sealevel",
+            Protocol::Cosmos => "cosmos",
+            Protocol::CosmosNative => "cosmosnative",
+            Protocol::Fuel => "fuel",
+            Protocol::Starknet => "starknet",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUrl {
+    pub http: String,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<u64>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Every problem found in a set of agent configs
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct InvalidConfig {
+    pub problems: Vec<String>,
+}
+
+impl Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid agent config ({} problems):",
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  * {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse, merge, and validate a set of agent config files
+///
+/// Files are merged in order, the same way the relayer does: objects are merged recursively, and
+/// anything else in a later file replaces the value in an earlier one.
+///
+/// # Errors
+///
+/// Every problem found, including:
+///
+/// * Malformed JSON
+/// * Fields of the wrong type
+/// * Missing required chain fields
+/// * Addresses that don't match the chain's protocol
+/// * Domain IDs used by more than one chain
+/// * An unknown `defaultRpcConsensusType`
+pub fn validate(configs: &[String]) -> Result<AgentConfig, InvalidConfig> {
+    let (config, mut problems) = merge_configs(configs);
+    problems.extend(config.problems());
+
+    if problems.is_empty() {
+        Ok(config)
+    } else {
+        Err(InvalidConfig { problems })
+    }
+}
+
+/// Parse and merge a set of agent config files, without validating them
+///
+/// Anything that fails to parse is left out of the config, and reported in the returned problems.
+pub fn merge_configs(configs: &[String]) -> (AgentConfig, Vec<String>) {
+    let mut problems = Vec::new();
+
+    let mut merged = Value::Object(Map::new());
+    for (index, config) in configs.iter().enumerate() {
+        match serde_json::from_str::<Value>(config) {
+            Ok(value @ Value::Object(_)) => merge(&mut merged, value),
+            Ok(_) => problems.push(format!("config {index}: expected a JSON object")),
+            Err(e) => problems.push(format!("config {index}: {e}")),
+        }
+    }
+
+    let Value::Object(mut merged) = merged else {
+        unreachable!("the merged config starts out as an object");
+    };
+
+    // Chains are parsed one by one, so a bad chain doesn't hide the problems with the others
+    let chains = match merged.remove("chains") {
+        Some(Value::Object(chains)) => chains,
+        Some(_) => {
+            problems.push(String::from("`chains`: expected an object"));
+            Map::new()
+        }
+        None => Map::new(),
+    };
+
+    let mut config = match serde_json::from_value::<AgentConfig>(Value::Object(merged)) {
+        Ok(config) => config,
+        Err(e) => {
+            problems.push(e.to_string());
+            AgentConfig::default()
+        }
+    };
+
+    for (name, chain) in chains {
+        match serde_json::from_value::<ChainConfig>(chain) {
+            Ok(chain) => {
+                config.chains.insert(name, chain);
+            }
+            Err(e) => problems.push(format!("chains.{name}: {e}")),
+        }
+    }
+
+    (config, problems)
+}
+
+impl AgentConfig {
+    /// Check the merged config for missing fields, bad addresses, and domain ID collisions
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(consensus_type) = &self.default_rpc_consensus_type
+            && let Err(e) = consensus_type.parse::<RpcConsensusType>()
+        {
+            problems.push(format!("defaultRpcConsensusType: {e}"));
+        }
+
+        let mut domains: HashMap<u32, Vec<&str>> = HashMap::new();
+        for (name, chain) in &self.chains {
+            let default = registry::default_chain(name);
+            problems.extend(
+                chain
+                    .problems(default)
+                    .into_iter()
+                    .map(|problem| format!("chains.{name}: {problem}")),
+            );
+
+            if let Some(chain_name) = &chain.name
+                && chain_name != name
+            {
+                problems.push(format!(
+                    "chains.{name}: `name` is `{chain_name}`, but must match the chain's key"
+                ));
+            }
+
+            if let Some(domain_id) = chain.domain_id.or(default.map(|default| default.domain_id)) {
+                domains.entry(domain_id).or_default().push(name);
+            }
+        }
+
+        let mut collisions = domains
+            .into_iter()
+            .filter(|(_, chains)| chains.len() > 1)
+            .collect::<Vec<_>>();
+        collisions.sort_unstable();
+        for (domain_id, chains) in collisions {
+            problems.push(format!(
+                "domain ID {domain_id} is used by multiple chains: {}",
+                chains.join(", ")
+            ));
+        }
+
+        problems
+    }
+}
+
+impl ChainConfig {
+    /// Check a single chain
+    ///
+    /// Chains in the relayer's default config only override the fields they set, so fields are
+    /// only required for chains without a `default`.
+    fn problems(&self, default: Option<&DefaultChain>) -> Vec<String> {
+        let mut problems = Vec::new();
+        let required = default.is_none();
+
+        if required && self.domain_id.is_none() {
+            problems.push(String::from("missing `domainId`"));
+        }
+
+        match &self.rpc_urls {
+            None if required => problems.push(String::from("missing `rpcUrls`")),
+            None => {}
+            Some(urls) if urls.is_empty() => problems.push(String::from("`rpcUrls` is empty")),
+            Some(urls) => {
+                for (index, RpcUrl { http, .. }) in urls.iter().enumerate() {
+                    match reqwest::Url::parse(http) {
+                        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                        Ok(url) => problems.push(format!(
+                            "rpcUrls[{index}]: unsupported scheme `{}`",
+                            url.scheme()
+                        )),
+                        Err(e) => problems.push(format!("rpcUrls[{index}]: `{http}` {e}")),
+                    }
+                }
+            }
+        }
+
+        let protocol = self.protocol.or(default.map(|default| default.protocol));
+        let contracts = [
+            ("mailbox", &self.mailbox),
+            ("merkleTreeHook", &self.merkle_tree_hook),
+            ("validatorAnnounce", &self.validator_announce),
+            ("interchainGasPaymaster", &self.interchain_gas_paymaster),
+        ];
+        for (field, address) in contracts {
+            let Some(address) = address else {
+                if required {
+                    problems.push(format!("missing `{field}`"));
+                }
+                continue;
+            };
+
+            if let Some(protocol) = protocol
+                && !is_valid_address(protocol, address)
+            {
+                problems.push(format!(
+                    "`{field}` is not a valid {protocol} address: `{address}`"
+                ));
+            }
+        }
+
+        if protocol.is_none() {
+            problems.push(String::from("missing `protocol`"));
+        }
+
+        if required && self.index.as_ref().and_then(|index| index.from).is_none() {
+            problems.push(String::from("missing `index.from`"));
+        }
+
+        problems
+    }
+}
+
+/// Parse a comma-separated list of relay chains
+///
+/// Names are trimmed and deduplicated, keeping their first position.
+///
+/// # Errors
+///
+/// * Any of the names are empty
+/// * There are fewer than two distinct chains
+pub fn parse_relay_chains(relay_chains: &str) -> Result<Vec<String>> {
+    let mut chains: Vec<String> = Vec::new();
+    for (index, chain) in relay_chains.split(',').enumerate() {
+        let chain = chain.trim();
+        if chain.is_empty() {
+            return Err(eyre!(
+                "`relay_chains` is invalid, chain {index} in `{relay_chains}` is empty"
+            ));
+        }
+
+        if !chains.iter().any(|existing| existing == chain) {
+            chains.push(chain.to_string());
+        }
+    }
+
+    if chains.len() < 2 {
+        return Err(eyre!(
+            "`relay_chains` is invalid, ensure it contains at least two chains"
+        ));
+    }
+
+    Ok(chains)
+}
+
+impl AgentConfig {
+    /// The protocol of `chain`, from this config or the relayer's defaults
+    pub fn protocol(&self, chain: &str) -> Option<Protocol> {
+        self.chains
+            .get(chain)
+            .and_then(|config| config.protocol)
+            .or_else(|| registry::default_chain(chain).map(|default| default.protocol))
+    }
+
+    /// The bech32 address prefix of a Cosmos `chain`, from this config or the relayer's defaults
+    pub fn bech32_prefix(&self, chain: &str) -> Option<String> {
+        self.chains
+            .get(chain)
+            .and_then(|config| config.other.get("bech32Prefix"))
+            .and_then(Value::as_str)
+            .map(String::from)
+            .or_else(|| {
+                registry::default_chain(chain).and_then(|default| default.bech32_prefix.clone())
+            })
+    }
+
+    /// Check that every relay chain is defined, either in this config or the relayer's defaults
+    ///
+    /// # Errors
+    ///
+    /// * Any of the chains are unknown, all of them are named
+    pub fn check_relay_chains(&self, relay_chains: &[String]) -> Result<()> {
+        let unknown = relay_chains
+            .iter()
+            .filter(|chain| {
+                !self.chains.contains_key(*chain) && registry::default_chain(chain).is_none()
+            })
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        Err(eyre!(
+            "Unknown relay chains: {}. Chains must be defined in the supplied configs, or be one of Hyperlane's default chains",
+            unknown.join(", ")
+        ))
+    }
+}
+
+/// Whether `address` is formatted correctly for `protocol`
+pub fn is_valid_address(protocol: Protocol, address: &str) -> bool {
+    match protocol {
+        Protocol::Ethereum => is_hex(address, 20),
+        // Base58 encoded 32 byte public keys
+        Protocol::Sealevel => {
+            (32..=44).contains(&address.len())
+                && address
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+        }
+        Protocol::Cosmos | Protocol::CosmosNative | Protocol::Fuel | Protocol::Starknet => {
+            is_hex(address, 32)
+        }
+    }
+}
+
+fn is_hex(address: &str, bytes: usize) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == bytes * 2 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Merge `overlay` into `base`, recursing into objects
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Render the config the relayer runs with for `relay_chains`
+///
+/// This is the bundled [default config](registry::default_config) of the relay chains, with
+/// `configs` merged over it in order, and the relay chains set. Signers are left out, since they
+/// come from the operator's keystore rather than the configs.
+///
+/// # Errors
+///
+/// * Any of the configs aren't JSON objects
+pub fn render(configs: &[String], relay_chains: &[String]) -> Result<String> {
+    let mut rendered = registry::default_config(relay_chains);
+    for (index, config) in configs.iter().enumerate() {
+        match serde_json::from_str::<Value>(config) {
+            Ok(value @ Value::Object(_)) => merge(&mut rendered, value),
+            Ok(_) => return Err(eyre!("config {index}: expected a JSON object")),
+            Err(e) => return Err(eyre!("config {index}: {e}")),
+        }
+    }
+
+    if let Some(Value::Object(chains)) = rendered.get_mut("chains") {
+        for chain in chains.values_mut() {
+            if let Value::Object(chain) = chain {
+                chain.remove("signer");
+            }
+        }
+    }
+    rendered["relayChains"] = Value::from(relay_chains.join(","));
+
+    Ok(serde_json::to_string_pretty(&rendered)?)
+}
@@ -0,0 +1,246 @@
+//! The relayer's signer balances
+//!
+//! A signer that runs out of gas on a destination chain silently stops delivering messages, so
+//! the native balance of the signer on every chain in the active agent config is checked
+//! periodically, and a warning is logged for any that fall below their threshold. Balances can
+//! also be swept to a new signer when it is rotated.
+//!
+//! The RPC URLs come from the service owner's configs, so they're held to the same rules as config
+//! URLs (see [`ConfigFetcher`](crate::fetch::ConfigFetcher)): unless the operator allows private
+//! IPs, they can't reach the operator's internal network.
+
+use crate::HyperlaneContext;
+use crate::agent_config::RpcUrl;
+use crate::fetch::{self, PublicResolver};
+use crate::signer::HexKey;
+use blueprint_sdk as sdk;
+use color_eyre::eyre::eyre;
+use color_eyre::{Report, Result};
+use reqwest::redirect::Policy;
+use sdk::alloy::network::{EthereumWallet, TransactionBuilder};
+use sdk::alloy::primitives::{Address, TxHash, U256};
+use sdk::alloy::providers::{Provider, ProviderBuilder};
+use sdk::alloy::rpc::client::RpcClient;
+use sdk::alloy::rpc::types::TransactionRequest;
+use sdk::alloy::transports::http::Http;
+use sdk::alloy::transports::http::reqwest::Url;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings for the signer balance monitor
+#[derive(Debug, Clone)]
+pub struct BalanceSettings {
+    /// How often to check the signer balances
+    pub interval: Duration,
+    /// The balance, in the smallest unit of the native token, below which a signer is low on funds
+    pub min_balance: U256,
+    /// Per-chain overrides of `min_balance`
+    pub min_balances: BTreeMap<String, U256>,
+    /// The timeout for each balance query
+    pub rpc_timeout: Duration,
+}
+
+impl Default for BalanceSettings {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(300),
+            // 0.1 of a native token with 18 decimals
+            min_balance: U256::from(100_000_000_000_000_000u64),
+            min_balances: BTreeMap::new(),
+            rpc_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl BalanceSettings {
+    /// The balance below which the signer on `chain` is low on funds
+    pub fn min_balance(&self, chain: &str) -> U256 {
+        self.min_balances
+            .get(chain)
+            .copied()
+            .unwrap_or(self.min_balance)
+    }
+}
+
+/// The HTTP client for requests to the service owner's RPC URLs
+#[derive(Debug, Clone)]
+pub struct RpcHttpClient {
+    client: reqwest::Client,
+    allow_private_ips: bool,
+}
+
+impl RpcHttpClient {
+    /// A client refusing non-public addresses, unless `allow_private_ips`
+    ///
+    /// # Errors
+    ///
+    /// * Unable to build the HTTP client
+    pub fn new(allow_private_ips: bool) -> Result<Self> {
+        // Redirects to IP addresses would skip the resolver
+        let mut builder = reqwest::Client::builder().redirect(Policy::none());
+        if !allow_private_ips {
+            // A proxy would resolve host names itself
+            builder = builder.no_proxy().dns_resolver(Arc::new(PublicResolver));
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            allow_private_ips,
+        })
+    }
+
+    fn url(&self, rpc_url: &RpcUrl) -> Result<Url> {
+        let url = rpc_url
+            .http
+            .parse()
+            .map_err(|e| eyre!("Invalid RPC URL `{}`: {e}", rpc_url.http))?;
+        if !self.allow_private_ips {
+            fetch::check_public_host(&url)?;
+        }
+        Ok(url)
+    }
+
+    fn rpc_client(&self, url: Url) -> RpcClient {
+        RpcClient::new(Http::with_client(self.client.clone(), url), false)
+    }
+}
+
+/// Query the native balance of `address`, trying each of `rpc_urls` in order
+///
+/// Errors may include the RPC's responses, so they're only meant for the operator.
+///
+/// # Errors
+///
+/// * None of the RPC URLs answered in time
+pub async fn query_balance(
+    client: &RpcHttpClient,
+    rpc_urls: &[RpcUrl],
+    address: Address,
+    timeout: Duration,
+) -> Result<U256> {
+    let mut last_error = eyre!("No RPC URLs configured");
+    for rpc_url in rpc_urls {
+        let url = match client.url(rpc_url) {
+            Ok(url) => url,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+
+        let provider = ProviderBuilder::new().on_client(client.rpc_client(url));
+        match sdk::tokio::time::timeout(timeout, provider.get_balance(address)).await {
+            Ok(Ok(balance)) => return Ok(balance),
+            Ok(Err(e)) => {
+                last_error =
+                    Report::new(e).wrap_err(format!("Balance query to `{}` failed", rpc_url.http));
+            }
+            Err(_) => last_error = eyre!("Balance query to `{}` timed out", rpc_url.http),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Transfer the whole native balance of `from` to `to`, less the transfer's fee
+///
+/// Each of `rpc_urls` is tried in order. Returns the hash of the transfer, or `None` if the
+/// balance doesn't cover the fee.
+///
+/// # Errors
+///
+/// * None of the RPC URLs could complete the transfer within `timeout`
+pub async fn sweep(
+    client: &RpcHttpClient,
+    rpc_urls: &[RpcUrl],
+    from: &HexKey,
+    to: Address,
+    timeout: Duration,
+) -> Result<Option<TxHash>> {
+    let mut last_error = eyre!("No RPC URLs configured");
+    for rpc_url in rpc_urls {
+        let url = match client.url(rpc_url) {
+            Ok(url) => url,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+
+        let rpc_client = client.rpc_client(url);
+        match sdk::tokio::time::timeout(timeout, sweep_via(rpc_client, from, to)).await {
+            Ok(Ok(tx_hash)) => return Ok(tx_hash),
+            Ok(Err(e)) => last_error = e.wrap_err(format!("Sweep via `{}` failed", rpc_url.http)),
+            Err(_) => last_error = eyre!("Sweep via `{}` timed out", rpc_url.http),
+        }
+    }
+
+    Err(last_error)
+}
+
+async fn sweep_via(rpc_client: RpcClient, from: &HexKey, to: Address) -> Result<Option<TxHash>> {
+    let wallet = from.wallet()?;
+    let from = wallet.address();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(wallet))
+        .on_client(rpc_client);
+
+    let balance = provider.get_balance(from).await?;
+    let gas_price = provider.get_gas_price().await?;
+    let transfer = TransactionRequest::default().with_from(from).with_to(to);
+    let gas = provider.estimate_gas(transfer.clone()).await?;
+
+    let fee = U256::from(gas) * U256::from(gas_price);
+    if balance <= fee {
+        return Ok(None);
+    }
+
+    let transfer = transfer
+        .with_value(balance - fee)
+        .with_gas_limit(gas)
+        .with_gas_price(gas_price);
+    let receipt = provider
+        .send_transaction(transfer)
+        .await?
+        .get_receipt()
+        .await?;
+    if !receipt.status() {
+        return Err(eyre!("Transfer {} was reverted", receipt.transaction_hash));
+    }
+
+    Ok(Some(receipt.transaction_hash))
+}
+
+/// Check the signer balances every [`BalanceSettings::interval`], warning about any that are low
+pub(crate) async fn monitor(ctx: HyperlaneContext) {
+    let mut interval = sdk::tokio::time::interval(ctx.settings.balances.interval);
+    loop {
+        interval.tick().await;
+
+        if ctx.active_config_version().is_none() {
+            continue;
+        }
+
+        let balances = match ctx.signer_balances().await {
+            Ok(balances) => balances,
+            Err(e) => {
+                sdk::warn!("Failed to check signer balances: {e}");
+                continue;
+            }
+        };
+
+        // Failed queries are logged by `signer_balances`
+        for balance in balances {
+            if balance.low {
+                sdk::warn!(
+                    "Signer {} on `{}` is low on funds: {} < {}",
+                    balance.address,
+                    balance.chain,
+                    balance.balance,
+                    balance.min_balance
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Agent configs in JSON or YAML
+//!
+//! The relayer only reads JSON, but Hyperlane's tooling and registry produce YAML, so YAML configs
+//! are converted before they're stored.
+
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use serde_json::Value;
+
+/// The format of a config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format of a config from its content type, or the extension of its path
+    ///
+    /// Returns `None` if neither gives it away.
+    pub fn detect(content_type: Option<&str>, path: &str) -> Option<Self> {
+        let mime = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(|mime| mime.trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some("application/json") => return Some(Self::Json),
+            Some(mime) if mime.ends_with("+json") => return Some(Self::Json),
+            Some("application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml") => {
+                return Some(Self::Yaml);
+            }
+            _ => {}
+        }
+
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("json") => Some(Self::Json),
+            Some("yaml" | "yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a config to the relayer's JSON format
+///
+/// JSON configs are returned as is. Configs of an unknown `format` are parsed as JSON, then as
+/// YAML.
+///
+/// # Errors
+///
+/// * The config isn't valid in its format, or in either format if it's unknown
+/// * The config isn't an object
+pub fn to_json(config: String, format: Option<ConfigFormat>) -> Result<String> {
+    let (value, format) = match format {
+        Some(ConfigFormat::Json) => (
+            serde_json::from_str::<Value>(&config).wrap_err("Config is not valid JSON")?,
+            ConfigFormat::Json,
+        ),
+        Some(ConfigFormat::Yaml) => (
+            serde_yaml::from_str::<Value>(&config).wrap_err("Config is not valid YAML")?,
+            ConfigFormat::Yaml,
+        ),
+        None => match serde_json::from_str::<Value>(&config) {
+            Ok(value) => (value, ConfigFormat::Json),
+            Err(_) => (
+                serde_yaml::from_str::<Value>(&config)
+                    .map_err(|_| eyre!("Config is neither valid JSON nor valid YAML"))?,
+                ConfigFormat::Yaml,
+            ),
+        },
+    };
+    // Almost any text is a valid YAML scalar
+    if !value.is_object() {
+        return Err(eyre!("Config must be an object"));
+    }
+
+    match format {
+        ConfigFormat::Json => Ok(config),
+        ConfigFormat::Yaml => Ok(serde_json::to_string_pretty(&value)?),
+    }
+}
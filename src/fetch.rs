@@ -0,0 +1,683 @@
+//! Fetching the agent configs passed to `set_config`
+//!
+//! A config URL can be pinned to the hash of its contents with a `#sha256=<hex>` or
+//! `#keccak256=<hex>` fragment. The downloaded config is checked against the pin before it's
+//! stored, so the relayer only ever runs the config the service owner audited.
+//!
+//! Configs can also be published on IPFS, as `ipfs://<cid>` URLs, see [`ipfs`], or staged on the
+//! operator's disk, see [`local_config`].
+//!
+//! Fetching is hardened against hostile URLs, see [`ConfigFetcher`].
+
+use crate::config_format::{self, ConfigFormat};
+use crate::inline_config::MAX_CONFIG_SIZE;
+use crate::ipfs;
+use crate::local_config;
+use crate::registry::layout::{self, RegistryChain};
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use futures::{StreamExt, TryStreamExt};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
+use sdk::alloy::primitives::{B256, keccak256};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A hash of a config's contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentHash {
+    Sha256(B256),
+    Keccak256(B256),
+}
+
+impl ContentHash {
+    pub fn sha256(contents: &[u8]) -> Self {
+        Self::Sha256(B256::from(<[u8; 32]>::from(Sha256::digest(contents))))
+    }
+
+    pub fn keccak256(contents: &[u8]) -> Self {
+        Self::Keccak256(keccak256(contents))
+    }
+
+    /// Check that `contents` hash to this
+    ///
+    /// # Errors
+    ///
+    /// * The hash of `contents` doesn't match
+    pub fn verify(&self, contents: &[u8]) -> Result<()> {
+        let actual = match self {
+            Self::Sha256(_) => Self::sha256(contents),
+            Self::Keccak256(_) => Self::keccak256(contents),
+        };
+
+        if actual != *self {
+            return Err(eyre!("Expected {self}, got {actual}"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256(hash) => write!(f, "sha256={hash}"),
+            Self::Keccak256(hash) => write!(f, "keccak256={hash}"),
+        }
+    }
+}
+
+impl FromStr for ContentHash {
+    type Err = color_eyre::Report;
+
+    /// Parse an `<algorithm>=<hex>` pair, with `sha256` or `keccak256` as the algorithm
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((algorithm, hash)) = s.split_once('=') else {
+            return Err(eyre!("Expected `<algorithm>=<hash>`, got `{s}`"));
+        };
+
+        let hash = hash
+            .parse::<B256>()
+            .map_err(|e| eyre!("Invalid {algorithm} hash `{hash}`: {e}"))?;
+        match algorithm {
+            "sha256" => Ok(Self::Sha256(hash)),
+            "keccak256" => Ok(Self::Keccak256(hash)),
+            _ => Err(eyre!(
+                "Unsupported hash algorithm `{algorithm}`, expected `sha256` or `keccak256`"
+            )),
+        }
+    }
+}
+
+/// The prefix of URLs pointing to a Hyperlane registry, rather than an agent config
+const REGISTRY_PREFIX: &str = "registry+";
+
+/// A config URL, with the hash its contents are pinned to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigUrl {
+    /// The URL, without the pin or registry prefix
+    pub url: Url,
+    pub pin: Option<ContentHash>,
+    /// Whether the URL points to a Hyperlane registry, see [`registry::layout`]
+    pub registry: bool,
+}
+
+impl FromStr for ConfigUrl {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (registry, unprefixed) = match s.strip_prefix(REGISTRY_PREFIX) {
+            Some(unprefixed) => (true, unprefixed),
+            None => (false, s),
+        };
+        let mut url =
+            Url::parse(unprefixed).wrap_err_with(|| format!("Invalid config URL `{s}`"))?;
+
+        // Any other fragment is left alone
+        let pin = match url.fragment() {
+            Some(fragment) if fragment.contains('=') => Some(
+                fragment
+                    .parse::<ContentHash>()
+                    .wrap_err_with(|| format!("Invalid pin in config URL `{s}`"))?,
+            ),
+            _ => None,
+        };
+        if pin.is_some() {
+            url.set_fragment(None);
+        }
+
+        Ok(Self { url, pin, registry })
+    }
+}
+
+/// A config fetched from a [`ConfigUrl`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedConfig {
+    /// The config, converted to JSON
+    pub config: String,
+    /// The hash of the contents as downloaded, before any conversion, `None` for registry
+    /// directories, which are downloaded file by file
+    pub hash: Option<ContentHash>,
+}
+
+/// Settings for fetching configs
+#[derive(Debug, Clone)]
+pub struct FetchSettings {
+    /// The timeout for each request, including reading the body
+    pub request_timeout: Duration,
+    /// The timeout for fetching all of a job's configs
+    pub total_timeout: Duration,
+    /// The largest config accepted
+    pub max_body_size: usize,
+    /// The most config URLs accepted in a single job
+    pub max_urls: usize,
+    /// The most redirects followed for a single config
+    pub max_redirects: usize,
+    /// The most configs downloaded at once
+    pub max_parallel: usize,
+    /// How many times a download is retried after a transient error
+    pub retries: u32,
+    /// The delay before the first retry, doubled for every following one
+    pub retry_backoff: Duration,
+    /// Whether to allow plain `http://` URLs
+    pub allow_http: bool,
+    /// Whether to allow URLs that resolve to loopback, private, link-local and other non-public
+    /// addresses
+    pub allow_private_ips: bool,
+    /// The gateway `ipfs://` URLs are fetched from, see [`ipfs`](crate::ipfs)
+    pub ipfs_gateway: Option<Url>,
+    /// The directory `local:` and `file://` URLs are read from, see [`local_config`]
+    pub local_config_dir: Option<PathBuf>,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(30),
+            total_timeout: Duration::from_secs(120),
+            max_body_size: MAX_CONFIG_SIZE,
+            max_urls: 16,
+            max_redirects: 5,
+            max_parallel: 4,
+            retries: 3,
+            retry_backoff: Duration::from_millis(500),
+            allow_http: false,
+            allow_private_ips: false,
+            ipfs_gateway: None,
+            local_config_dir: None,
+        }
+    }
+}
+
+/// Fetches configs with a shared client, within the limits of its [`FetchSettings`]
+///
+/// Unless private IPs are allowed, host names are resolved by [`PublicResolver`], which refuses
+/// to hand out non-public addresses, so neither a config URL nor a redirect can reach the
+/// operator's internal network. The IPFS gateway is chosen by the operator, so it's exempt, and
+/// it's trusted for availability only: everything it returns is checked against the CID.
+#[derive(Debug, Clone)]
+pub struct ConfigFetcher {
+    client: reqwest::Client,
+    gateway_client: reqwest::Client,
+    settings: FetchSettings,
+    /// Whether `file://` URLs can point outside of the local config directory
+    test_mode: bool,
+}
+
+impl ConfigFetcher {
+    /// A fetcher enforcing `settings`, allowing any `file://` URL in `test_mode`
+    ///
+    /// # Errors
+    ///
+    /// * Unable to build the HTTP client
+    pub fn new(settings: FetchSettings, test_mode: bool) -> Result<Self> {
+        let redirect_settings = settings.clone();
+        let redirect = Policy::custom(move |attempt| {
+            if attempt.previous().len() > redirect_settings.max_redirects {
+                return attempt.error(format!(
+                    "Too many redirects (limit {})",
+                    redirect_settings.max_redirects
+                ));
+            }
+            match check_url(attempt.url(), &redirect_settings) {
+                Ok(()) => attempt.follow(),
+                Err(e) => attempt.error(e.to_string()),
+            }
+        });
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(settings.request_timeout)
+            .connect_timeout(settings.request_timeout)
+            .redirect(redirect)
+            .https_only(!settings.allow_http);
+        if !settings.allow_private_ips {
+            // A proxy would resolve host names itself
+            builder = builder.no_proxy().dns_resolver(Arc::new(PublicResolver));
+        }
+
+        let gateway_client = reqwest::Client::builder()
+            .timeout(settings.request_timeout)
+            .connect_timeout(settings.request_timeout)
+            .redirect(Policy::limited(settings.max_redirects))
+            .build()?;
+
+        Ok(Self {
+            client: builder.build()?,
+            gateway_client,
+            settings,
+            test_mode,
+        })
+    }
+
+    /// Download the configs at `config_urls`, checking them against their pins
+    ///
+    /// Up to [`FetchSettings::max_parallel`] configs are downloaded at once, and they're returned
+    /// in the order of `config_urls`. Configs are built for `relay_chains` from registry URLs, see
+    /// [`Self::fetch_registry`].
+    ///
+    /// # Errors
+    ///
+    /// * There are more than [`FetchSettings::max_urls`] URLs
+    /// * The downloads took longer than [`FetchSettings::total_timeout`]
+    /// * Any download failed, see [`Self::fetch`]
+    pub async fn fetch_all(
+        &self,
+        config_urls: &[ConfigUrl],
+        relay_chains: &[String],
+    ) -> Result<Vec<FetchedConfig>> {
+        if config_urls.len() > self.settings.max_urls {
+            return Err(eyre!(
+                "Too many config URLs ({}), the limit is {}",
+                config_urls.len(),
+                self.settings.max_urls
+            ));
+        }
+
+        // `buffered` yields in order, however the downloads finish
+        let downloads = config_urls
+            .iter()
+            .map(|config_url| async move {
+                if config_url.registry {
+                    self.fetch_registry(config_url, relay_chains).await
+                } else {
+                    self.fetch_config(config_url).await
+                }
+            })
+            .collect::<Vec<_>>();
+        let fetch_all = futures::stream::iter(downloads)
+            .buffered(self.settings.max_parallel.max(1))
+            .try_collect::<Vec<_>>();
+        sdk::tokio::time::timeout(self.settings.total_timeout, fetch_all)
+            .await
+            .map_err(|_| {
+                eyre!(
+                    "Fetching configs took longer than {:?}",
+                    self.settings.total_timeout
+                )
+            })?
+    }
+
+    /// Download the config at `config_url`, checking it against its pin
+    ///
+    /// `ipfs://` URLs are fetched from [`FetchSettings::ipfs_gateway`], and `local:` and `file://`
+    /// URLs are read from [`FetchSettings::local_config_dir`]. In test mode, `file://` URLs can
+    /// point anywhere.
+    ///
+    /// # Errors
+    ///
+    /// * The URL isn't allowed, see [`FetchSettings`]
+    /// * The download failed, or timed out
+    /// * The config is larger than [`FetchSettings::max_body_size`]
+    /// * The config doesn't match its pin
+    /// * The config isn't valid UTF-8
+    /// * The config isn't a JSON or YAML object, see [`config_format::to_json`]
+    pub async fn fetch(&self, config_url: &ConfigUrl) -> Result<String> {
+        Ok(self.fetch_config(config_url).await?.config)
+    }
+
+    /// [`Self::fetch`], along with the hash of the downloaded contents
+    async fn fetch_config(&self, config_url: &ConfigUrl) -> Result<FetchedConfig> {
+        let url = &config_url.url;
+        if config_url.registry {
+            return Err(eyre!("`{url}` is a registry, not a config"));
+        }
+
+        let (body, content_type, hash) = self.fetch_bytes(config_url).await?;
+        let config = String::from_utf8(body)
+            .wrap_err_with(|| format!("Config at `{url}` is not valid UTF-8"))?;
+        let format = ConfigFormat::detect(content_type.as_deref(), url.path());
+        let config = config_format::to_json(config, format)
+            .wrap_err_with(|| format!("Invalid config at `{url}`"))?;
+
+        Ok(FetchedConfig {
+            config,
+            hash: Some(hash),
+        })
+    }
+
+    /// Build an agent config for `relay_chains` from the registry at `config_url`
+    ///
+    /// The registry is either a directory, with the chain files fetched from
+    /// `<url>/chains/<chain>/`, or a `.tar`, `.tar.gz` or `.tgz` archive of one. Only archives
+    /// can be pinned, and only archives have a [`FetchedConfig::hash`].
+    ///
+    /// # Errors
+    ///
+    /// * A chain file or the archive couldn't be fetched, see [`Self::fetch`]
+    /// * The agent config couldn't be built, see [`layout::build_agent_config`]
+    pub async fn fetch_registry(
+        &self,
+        config_url: &ConfigUrl,
+        relay_chains: &[String],
+    ) -> Result<FetchedConfig> {
+        let url = &config_url.url;
+        let is_archive = [".tar", ".tar.gz", ".tgz"]
+            .iter()
+            .any(|extension| url.path().ends_with(extension));
+
+        let (chains, hash) = if is_archive {
+            let (archive, _, hash) = self.fetch_bytes(config_url).await?;
+            let chains = layout::read_archive(&archive, relay_chains)
+                .wrap_err_with(|| format!("Invalid registry archive at `{url}`"))?;
+            (chains, Some(hash))
+        } else {
+            if config_url.pin.is_some() {
+                return Err(eyre!(
+                    "Registry `{url}` is a directory, only registry archives can be pinned"
+                ));
+            }
+
+            let root = url.as_str().trim_end_matches('/');
+            let mut files = Vec::new();
+            for chain in relay_chains {
+                for file in [layout::METADATA_FILE, layout::ADDRESSES_FILE] {
+                    let file_url = format!("{root}/{}", layout::chain_file(chain, file)?)
+                        .parse::<ConfigUrl>()?;
+                    files.push(async move {
+                        let contents = self.fetch(&file_url).await?;
+                        let value = layout::parse_file(chain, file, contents.as_bytes())?;
+                        Ok::<_, color_eyre::Report>((chain, file, value))
+                    });
+                }
+            }
+
+            let mut chains = BTreeMap::<String, RegistryChain>::new();
+            let files = futures::stream::iter(files)
+                .buffered(self.settings.max_parallel.max(1))
+                .try_collect::<Vec<_>>()
+                .await?;
+            for (chain, file, value) in files {
+                chains.entry(chain.clone()).or_default().insert(file, value);
+            }
+            (chains, None)
+        };
+
+        let config = layout::build_agent_config(relay_chains, &chains)
+            .wrap_err_with(|| format!("Invalid registry at `{url}`"))?;
+        Ok(FetchedConfig { config, hash })
+    }
+
+    /// Download the contents at `config_url`, checking them against its pin
+    ///
+    /// Returns the contents, their content type if they were served over HTTP, and their hash: the
+    /// pin if there is one, otherwise their keccak256 hash.
+    async fn fetch_bytes(
+        &self,
+        config_url: &ConfigUrl,
+    ) -> Result<(Vec<u8>, Option<String>, ContentHash)> {
+        let url = &config_url.url;
+
+        // https://github.com/seanmonstar/reqwest/issues/178
+        let (body, content_type) = if url.scheme() == "file" && self.test_mode {
+            let path = url
+                .to_file_path()
+                .map_err(|()| eyre!("Invalid file URL `{url}`"))?;
+            (std::fs::read(path)?, None)
+        } else if matches!(url.scheme(), "file" | "local") {
+            (self.read_local(url)?, None)
+        } else if url.scheme() == "ipfs" {
+            let cid = ipfs::parse_url(url)?;
+            let gateway = self
+                .settings
+                .ipfs_gateway
+                .as_ref()
+                .ok_or_else(|| eyre!("No IPFS gateway is configured for `{url}`"))?;
+            let (block, _) = self
+                .download_with_retries(&self.gateway_client, &ipfs::gateway_url(gateway, &cid)?)
+                .await
+                .wrap_err_with(|| format!("Failed to fetch config from `{url}`"))?;
+            // The gateway's content type is for the raw block
+            (ipfs::verify(&cid, block)?, None)
+        } else {
+            check_url(url, &self.settings)?;
+            self.download_with_retries(&self.client, url)
+                .await
+                .wrap_err_with(|| format!("Failed to fetch config from `{url}`"))?
+        };
+
+        let hash = match config_url.pin {
+            Some(pin) => {
+                pin.verify(&body)
+                    .wrap_err_with(|| format!("Config at `{url}` does not match its pin"))?;
+                pin
+            }
+            None => ContentHash::keccak256(&body),
+        };
+
+        Ok((body, content_type, hash))
+    }
+
+    /// Read a config from [`FetchSettings::local_config_dir`], see [`local_config`]
+    fn read_local(&self, url: &Url) -> Result<Vec<u8>> {
+        let dir = self
+            .settings
+            .local_config_dir
+            .as_deref()
+            .ok_or_else(|| eyre!("No local config directory is configured for `{url}`"))?;
+        let path = local_config::resolve(dir, url)?;
+
+        let max_body_size = self.settings.max_body_size;
+        if std::fs::metadata(&path)?.len() > max_body_size as u64 {
+            return Err(eyre!("Config `{url}` is larger than {max_body_size} bytes"));
+        }
+
+        Ok(std::fs::read(path)?)
+    }
+
+    /// Download `url`, retrying up to [`FetchSettings::retries`] times after transient errors
+    async fn download_with_retries(
+        &self,
+        client: &reqwest::Client,
+        url: &Url,
+    ) -> Result<(Vec<u8>, Option<String>)> {
+        let mut attempt = 0;
+        loop {
+            match self.download(client, url).await {
+                Ok(download) => return Ok(download),
+                Err(e) if attempt < self.settings.retries && is_transient(&e) => {
+                    let delay = self
+                        .settings
+                        .retry_backoff
+                        .saturating_mul(2u32.saturating_pow(attempt));
+                    attempt += 1;
+                    sdk::warn!(
+                        "Failed to fetch config from `{url}`, retrying in {delay:?} ({attempt}/{}): {e}",
+                        self.settings.retries
+                    );
+                    sdk::tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Download `url`, returning the body and its content type
+    async fn download(
+        &self,
+        client: &reqwest::Client,
+        url: &Url,
+    ) -> Result<(Vec<u8>, Option<String>)> {
+        let max_body_size = self.settings.max_body_size;
+        let too_large = || eyre!("Config is larger than {max_body_size} bytes");
+
+        let mut response = client.get(url.clone()).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_body_size as u64)
+        {
+            return Err(too_large());
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(String::from);
+
+        // The length may be missing or wrong, so it's enforced while reading as well
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > max_body_size {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok((body, content_type))
+    }
+}
+
+/// Whether a failed download is worth retrying
+///
+/// Connection failures, timeouts, rate limiting and server errors are transient. Anything else,
+/// like a missing config or a blocked URL, would fail the same way again.
+fn is_transient(error: &color_eyre::Report) -> bool {
+    let Some(error) = error.downcast_ref::<reqwest::Error>() else {
+        return false;
+    };
+
+    // Blocked hosts surface as connection errors
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        if e.is::<NonPublicHost>() {
+            return false;
+        }
+        source = e.source();
+    }
+
+    match error.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => error.is_connect() || error.is_timeout() || error.is_body(),
+    }
+}
+
+/// Check the scheme of `url`, and its host if it's an IP address
+///
+/// Host names are checked when they're resolved, see [`PublicResolver`].
+fn check_url(url: &Url, settings: &FetchSettings) -> Result<()> {
+    match url.scheme() {
+        "https" => {}
+        "http" if settings.allow_http => {}
+        scheme => return Err(eyre!("Config URL scheme `{scheme}` is not allowed")),
+    }
+
+    if !settings.allow_private_ips {
+        check_public_host(url)?;
+    }
+
+    Ok(())
+}
+
+/// Check that the host of `url` isn't a non-public IP address
+///
+/// IP addresses are connected to as is, host names are checked as they're resolved by
+/// [`PublicResolver`].
+///
+/// # Errors
+///
+/// * The URL has no host, or its host is a non-public IP address
+pub fn check_public_host(url: &Url) -> Result<()> {
+    let Some(host) = url.host_str() else {
+        return Err(eyre!("URL `{url}` has no host"));
+    };
+    // IPv6 hosts are bracketed
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>()
+        && !is_public(ip)
+    {
+        return Err(eyre!("URL `{url}` points to a non-public address"));
+    }
+
+    Ok(())
+}
+
+/// A DNS resolver that only returns public addresses
+///
+/// Resolving and connecting happen in one step, so a host name can't be rebound to a private
+/// address between the check and the request.
+#[derive(Debug, Clone, Copy)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs = sdk::tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+            if addrs.is_empty() {
+                return Err(Box::new(NonPublicHost(host.to_string())) as _);
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// A host name that doesn't resolve to any public address
+#[derive(Debug)]
+struct NonPublicHost(String);
+
+impl Display for NonPublicHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` does not resolve to a public address", self.0)
+    }
+}
+
+impl std::error::Error for NonPublicHost {}
+
+/// Whether `ip` is a publicly routable address
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_v4(ip);
+            }
+
+            let segments = ip.segments();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local, fc00::/7
+                || (segments[0] & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (segments[0] & 0xffc0) == 0xfe80
+                // Documentation, 2001:db8::/32
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+                // IPv4-compatible and NAT64, which can reach IPv4 addresses
+                || (segments[..6] == [0; 6])
+                || (segments[0] == 0x64 && segments[1] == 0xff9b))
+        }
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "This network", 0.0.0.0/8
+        || a == 0
+        // Shared address space, 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved, 240.0.0.0/4
+        || a >= 240)
+}
@@ -0,0 +1,429 @@
+//! Versioned history of the configs applied with `set_config`
+//!
+//! Every set of configs is stored as a numbered generation under `data_dir/configs`:
+//!
+//! ```text
+//! configs/
+//! ├── 1/
+//! │   ├── agent_configs/
+//! │   │   ├── 0.json
+//! │   │   ├── 1.json
+//! │   │   └── rpc_urls.json
+//! │   ├── relay_chains.txt
+//! │   └── meta.json
+//! └── 2/
+//!     └── ...
+//! ```
+//!
+//! `rpc_urls.json` is only there for generations created with `set_rpc_urls`.
+//!
+//! The generation in use is tracked in the [`RelayerState`](crate::state::RelayerState).
+
+use crate::fetch::ContentHash;
+use crate::state::unix_timestamp;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use sdk::alloy::primitives::{B256, Keccak256};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const AGENT_CONFIGS_DIR: &str = "agent_configs";
+/// The overlay of RPC overrides, see [`rpc_urls`](crate::rpc_urls)
+///
+/// Configs are applied by their position, and this sorts after all of them.
+pub const RPC_URLS_FILE: &str = "rpc_urls.json";
+const RELAY_CHAINS_FILE: &str = "relay_chains.txt";
+const META_FILE: &str = "meta.json";
+/// New generations are written here, and renamed into place once complete
+const STAGING_DIR: &str = ".staging";
+
+/// Details about how a generation was created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationMeta {
+    pub version: u64,
+    /// When the generation was created, in seconds since the UNIX epoch
+    pub timestamp: u64,
+    /// The ID of the job call that created the generation
+    pub call_id: Option<u64>,
+    /// The account that submitted the job call
+    pub caller: Option<String>,
+    /// See [`config_hash`]
+    pub config_hash: String,
+    /// Where each of the agent configs came from, in order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ConfigSource>,
+}
+
+/// Where an agent config came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSource {
+    /// The URL the config was fetched from, `None` if it was passed inline
+    pub url: Option<String>,
+    /// The hash of the config as it was fetched or passed, before any conversion, see
+    /// [`FetchedConfig::hash`](crate::fetch::FetchedConfig::hash)
+    ///
+    /// This is the pin if the config was pinned, otherwise its keccak256 hash. `None` if there were
+    /// no such contents, like for registry directories.
+    pub hash: Option<String>,
+    /// Whether the config was checked against a pinned hash
+    pub pinned: bool,
+    /// The keccak256 hash of the config as stored in the generation
+    #[serde(default)]
+    pub stored_hash: String,
+}
+
+impl ConfigSource {
+    /// The source of `config`, from contents hashing to `hash`, that were checked against it if
+    /// `pinned`
+    pub fn new(url: Option<String>, hash: Option<ContentHash>, pinned: bool, config: &str) -> Self {
+        Self {
+            url,
+            hash: hash.map(|hash| hash.to_string()),
+            pinned,
+            stored_hash: ContentHash::keccak256(config.as_bytes()).to_string(),
+        }
+    }
+}
+
+/// The origin of a new generation
+#[derive(Debug, Clone, Default)]
+pub struct Origin {
+    pub call_id: Option<u64>,
+    pub caller: Option<String>,
+    /// See [`GenerationMeta::sources`]
+    pub sources: Vec<ConfigSource>,
+}
+
+/// The store of config generations
+#[derive(Debug, Clone)]
+pub struct ConfigHistory {
+    root: PathBuf,
+    retain: usize,
+}
+
+impl ConfigHistory {
+    /// A history at `root`, keeping at most `retain` generations
+    pub fn new(root: PathBuf, retain: usize) -> Self {
+        Self { root, retain }
+    }
+
+    pub fn path(&self, version: u64) -> PathBuf {
+        self.root.join(version.to_string())
+    }
+
+    pub fn agent_configs_path(&self, version: u64) -> PathBuf {
+        self.path(version).join(AGENT_CONFIGS_DIR)
+    }
+
+    pub fn rpc_urls_path(&self, version: u64) -> PathBuf {
+        self.agent_configs_path(version).join(RPC_URLS_FILE)
+    }
+
+    pub fn relay_chains_path(&self, version: u64) -> PathBuf {
+        self.path(version).join(RELAY_CHAINS_FILE)
+    }
+
+    /// The chains relayed with `version`, none if it has no relay chains stored
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the relay chains of `version`
+    pub fn relay_chains(&self, version: u64) -> Result<Vec<String>> {
+        let relay_chains_path = self.relay_chains_path(version);
+        if !relay_chains_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(std::fs::read_to_string(relay_chains_path)?
+            .split(',')
+            .map(|chain| chain.trim().to_string())
+            .collect())
+    }
+
+    /// The agent config files of `version`, in the order they're applied
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the generation's configs
+    pub fn config_files(&self, version: u64) -> Result<Vec<PathBuf>> {
+        let agent_configs_path = self.agent_configs_path(version);
+        if !agent_configs_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = std::fs::read_dir(agent_configs_path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter(|path| path.as_ref().map_or(true, |path| path.is_file()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        // Configs are named after their position, `10.json` comes after `9.json`
+        files.sort_by_key(|path| {
+            let index = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok());
+            (index.is_none(), index, path.clone())
+        });
+        Ok(files)
+    }
+
+    pub fn contains(&self, version: u64) -> bool {
+        self.path(version).join(META_FILE).exists()
+    }
+
+    /// All retained versions, oldest first
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the history directory
+    pub fn versions(&self) -> Result<Vec<u64>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut versions = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let Some(version) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u64>().ok())
+            else {
+                continue;
+            };
+
+            if self.contains(version) {
+                versions.push(version);
+            }
+        }
+
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    /// Read the metadata of `version`
+    ///
+    /// # Errors
+    ///
+    /// * The generation doesn't exist
+    /// * The metadata is malformed
+    pub fn meta(&self, version: u64) -> Result<GenerationMeta> {
+        let meta = std::fs::read(self.path(version).join(META_FILE))
+            .map_err(|e| eyre!("Config version {version} not found: {e}"))?;
+        Ok(serde_json::from_slice(&meta)?)
+    }
+
+    /// Store a new generation, returning its version
+    ///
+    /// The generation is written to a staging directory and moved into place with a single
+    /// rename, so a crash part way through never leaves a partial generation behind.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the generation to disk
+    pub fn create(&self, configs: &[String], relay_chains: &str, origin: Origin) -> Result<u64> {
+        self.create_with_rpc_urls(configs, None, relay_chains, origin)
+    }
+
+    /// Store a new generation with an overlay of RPC overrides, returning its version
+    ///
+    /// See [`Self::create`].
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the generation to disk
+    pub fn create_with_rpc_urls(
+        &self,
+        configs: &[String],
+        rpc_urls: Option<&str>,
+        relay_chains: &str,
+        origin: Origin,
+    ) -> Result<u64> {
+        let version = self.next_version()?;
+
+        self.clean_staging()?;
+        let staging = self.staging_path();
+        let agent_configs_path = staging.join(AGENT_CONFIGS_DIR);
+        std::fs::create_dir_all(&agent_configs_path)?;
+
+        if configs.is_empty() {
+            sdk::info!("No configs provided, using defaults");
+        }
+        for (index, config) in configs.iter().enumerate() {
+            write_synced(
+                &agent_configs_path.join(format!("{index}.json")),
+                config.as_bytes(),
+            )?;
+        }
+        if let Some(rpc_urls) = rpc_urls {
+            write_synced(&agent_configs_path.join(RPC_URLS_FILE), rpc_urls.as_bytes())?;
+        }
+
+        write_synced(&staging.join(RELAY_CHAINS_FILE), relay_chains.as_bytes())?;
+        write_meta(&staging, version, origin)?;
+
+        // A directory without metadata here is left over from an interrupted import
+        let path = self.path(version);
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+        std::fs::rename(&staging, &path)?;
+        sync_dir(&self.root)?;
+
+        sdk::info!(
+            "Config version {version} written to: {}",
+            self.path(version).display()
+        );
+        Ok(version)
+    }
+
+    /// Remove a generation left part way written by a crash
+    ///
+    /// # Errors
+    ///
+    /// * Unable to remove the staging directory
+    pub fn clean_staging(&self) -> Result<()> {
+        let staging = self.staging_path();
+        if staging.exists() {
+            sdk::warn!("Removing incomplete config generation");
+            std::fs::remove_dir_all(staging)?;
+        }
+        Ok(())
+    }
+
+    /// Move configs applied before versioning was introduced into the history
+    ///
+    /// Returns the version they were stored as, if there were any.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to move the configs
+    pub fn import_legacy(&self, data_dir: &Path) -> Result<Option<u64>> {
+        let relay_chains_path = data_dir.join(RELAY_CHAINS_FILE);
+        if !relay_chains_path.exists() {
+            return Ok(None);
+        }
+
+        let version = self.next_version()?;
+        std::fs::create_dir_all(self.path(version))?;
+
+        let agent_configs_path = data_dir.join(AGENT_CONFIGS_DIR);
+        if agent_configs_path.exists() {
+            std::fs::rename(agent_configs_path, self.agent_configs_path(version))?;
+        } else {
+            std::fs::create_dir_all(self.agent_configs_path(version))?;
+        }
+        std::fs::rename(relay_chains_path, self.relay_chains_path(version))?;
+
+        // The old single backup scheme
+        for backup in ["agent_configs.orig", "relay_chains.txt.orig"] {
+            let path = data_dir.join(backup);
+            if path.is_dir() {
+                std::fs::remove_dir_all(path)?;
+            } else if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        write_meta(&self.path(version), version, Origin::default())?;
+        sdk::info!("Imported existing configs as config version {version}");
+        Ok(Some(version))
+    }
+
+    /// Remove the oldest generations beyond the retention limit, other than those in `keep`
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the history directory
+    pub fn prune(&self, keep: &[u64]) -> Result<()> {
+        let versions = self.versions()?;
+        let excess = versions.len().saturating_sub(self.retain);
+
+        for version in versions
+            .into_iter()
+            .filter(|version| !keep.contains(version))
+            .take(excess)
+        {
+            sdk::debug!("Pruning config version {version}");
+            if let Err(e) = std::fs::remove_dir_all(self.path(version)) {
+                sdk::warn!("Failed to prune config version {version}: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn next_version(&self) -> Result<u64> {
+        Ok(self.versions()?.last().map_or(1, |latest| latest + 1))
+    }
+
+    fn staging_path(&self) -> PathBuf {
+        self.root.join(STAGING_DIR)
+    }
+}
+
+fn write_meta(generation: &Path, version: u64, origin: Origin) -> Result<()> {
+    let meta = GenerationMeta {
+        version,
+        timestamp: unix_timestamp(),
+        call_id: origin.call_id,
+        caller: origin.caller,
+        config_hash: config_hash(
+            &generation.join(AGENT_CONFIGS_DIR),
+            &generation.join(RELAY_CHAINS_FILE),
+        )?
+        .to_string(),
+        sources: origin.sources,
+    };
+
+    // Written last, a generation without metadata is incomplete and ignored
+    write_synced(
+        &generation.join(META_FILE),
+        &serde_json::to_vec_pretty(&meta)?,
+    )
+}
+
+/// Write a file and flush it to disk
+pub(crate) fn write_synced(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Flush a directory's entries to disk, making a rename within it durable
+pub(crate) fn sync_dir(path: &Path) -> Result<()> {
+    File::open(path)?.sync_all()?;
+    Ok(())
+}
+
+/// A hash over a set of agent configs and relay chains
+///
+/// # Errors
+///
+/// * Unable to read the configs
+pub fn config_hash(agent_configs_path: &Path, relay_chains_path: &Path) -> Result<B256> {
+    let mut hasher = Keccak256::new();
+
+    if agent_configs_path.exists() {
+        let mut files = std::fs::read_dir(agent_configs_path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        files.sort();
+
+        for file in files.iter().filter(|path| path.is_file()) {
+            hasher.update(file.file_name().unwrap().as_encoded_bytes());
+            hasher.update(std::fs::read(file)?);
+        }
+    }
+
+    if relay_chains_path.exists() {
+        hasher.update(std::fs::read(relay_chains_path)?);
+    }
+
+    Ok(hasher.finalize())
+}
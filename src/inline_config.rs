@@ -0,0 +1,62 @@
+//! Agent configs submitted inline as job arguments
+//!
+//! Small configs can be put on-chain directly, instead of being hosted somewhere for `set_config`
+//! to fetch. The payload may be gzip compressed, and is capped in size both as submitted and once
+//! decompressed.
+
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+/// The largest config accepted once decompressed
+pub const MAX_CONFIG_SIZE: usize = 1024 * 1024;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `contents` are gzip compressed
+pub fn is_gzip(contents: &[u8]) -> bool {
+    contents.starts_with(&GZIP_MAGIC)
+}
+
+/// Read `reader` to the end, unless it holds more than `limit` bytes
+///
+/// Returns `None` if it does, having read one byte over the limit at most.
+///
+/// # Errors
+///
+/// * Unable to read from `reader`
+pub fn read_limited(reader: impl Read, limit: usize) -> std::io::Result<Option<Vec<u8>>> {
+    let mut contents = Vec::new();
+    // One byte over the limit, to tell contents of exactly the limit from larger ones
+    reader.take(limit as u64 + 1).read_to_end(&mut contents)?;
+    Ok((contents.len() <= limit).then_some(contents))
+}
+
+/// Decode an inline config payload of at most `limit` bytes, decompressing it if it's gzipped
+///
+/// # Errors
+///
+/// * The payload is larger than `limit`
+/// * The payload is gzipped, and is corrupt or decompresses to more than [`MAX_CONFIG_SIZE`]
+/// * The config isn't valid UTF-8
+pub fn decode(payload: Vec<u8>, limit: usize) -> Result<String> {
+    if payload.len() > limit {
+        return Err(eyre!(
+            "Inline config is {} bytes, the limit is {limit}",
+            payload.len()
+        ));
+    }
+
+    let config = if is_gzip(&payload) {
+        read_limited(GzDecoder::new(payload.as_slice()), MAX_CONFIG_SIZE)
+            .wrap_err("Inline config is not valid gzip")?
+            .ok_or_else(|| {
+                eyre!("Inline config decompresses to more than {MAX_CONFIG_SIZE} bytes")
+            })?
+    } else {
+        payload
+    };
+
+    String::from_utf8(config).wrap_err("Inline config is not valid UTF-8")
+}
@@ -0,0 +1,173 @@
+//! Configs published on IPFS
+//!
+//! An `ipfs://<cid>` config URL is fetched from the operator's gateway as a raw block, which is
+//! checked against the CID before it's used, so the gateway doesn't need to be trusted. Only
+//! single-block files are supported: either `raw` blocks (`ipfs block put`, or `ipfs add
+//! --raw-leaves` for small files) or `dag-pb` UnixFS files without links (`ipfs add` of a file
+//! under the chunk size).
+
+use crate::fetch::ContentHash;
+use blueprint_sdk as sdk;
+use cid::Cid;
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use reqwest::Url;
+use sdk::alloy::primitives::B256;
+
+const RAW: u64 = 0x55;
+const DAG_PB: u64 = 0x70;
+
+const SHA2_256: u64 = 0x12;
+const KECCAK_256: u64 = 0x1b;
+
+/// Parse the CID of an `ipfs://<cid>` URL
+///
+/// # Errors
+///
+/// * The URL isn't an `ipfs` URL
+/// * The CID is invalid
+/// * The URL has a path or query, which would need a directory to be resolved
+pub fn parse_url(url: &Url) -> Result<Cid> {
+    if url.scheme() != "ipfs" {
+        return Err(eyre!("`{url}` is not an IPFS URL"));
+    }
+    if !matches!(url.path(), "" | "/") || url.query().is_some() {
+        return Err(eyre!(
+            "IPFS URL `{url}` must point directly to a file, without a path or query"
+        ));
+    }
+
+    let cid = url
+        .host_str()
+        .ok_or_else(|| eyre!("IPFS URL `{url}` has no CID"))?;
+    Cid::try_from(cid).wrap_err_with(|| format!("Invalid CID in IPFS URL `{url}`"))
+}
+
+/// The URL of the raw block for `cid` on `gateway`, see the [trustless gateway spec]
+///
+/// [trustless gateway spec]: https://specs.ipfs.tech/http-gateways/trustless-gateway/
+///
+/// # Errors
+///
+/// * `gateway` can't be used as a base URL
+pub fn gateway_url(gateway: &Url, cid: &Cid) -> Result<Url> {
+    let mut url = gateway.clone();
+    url.path_segments_mut()
+        .map_err(|()| eyre!("Invalid IPFS gateway `{gateway}`"))?
+        .pop_if_empty()
+        .extend(["ipfs", &cid.to_string()]);
+    url.set_query(Some("format=raw"));
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Check `block` against `cid`, and extract the file it holds
+///
+/// # Errors
+///
+/// * The CID uses an unsupported codec or hash function
+/// * `block` doesn't match the CID
+/// * `block` isn't a single-block UnixFS file
+pub fn verify(cid: &Cid, block: Vec<u8>) -> Result<Vec<u8>> {
+    let hash = cid.hash();
+    let digest = B256::try_from(hash.digest())
+        .map_err(|_| eyre!("Unsupported {}-byte digest in CID {cid}", hash.size()))?;
+    let expected = match hash.code() {
+        SHA2_256 => ContentHash::Sha256(digest),
+        KECCAK_256 => ContentHash::Keccak256(digest),
+        code => {
+            return Err(eyre!(
+                "Unsupported hash function {code:#x} in CID {cid}, expected sha2-256 or keccak-256"
+            ));
+        }
+    };
+    expected
+        .verify(&block)
+        .wrap_err_with(|| format!("Block does not match CID {cid}"))?;
+
+    match cid.codec() {
+        RAW => Ok(block),
+        DAG_PB => unixfs_file(&block).wrap_err_with(|| format!("Invalid UnixFS file {cid}")),
+        codec => Err(eyre!(
+            "Unsupported codec {codec:#x} in CID {cid}, expected raw or dag-pb"
+        )),
+    }
+}
+
+/// Extract the contents of a single-block UnixFS file from a `dag-pb` node
+fn unixfs_file(node: &[u8]) -> Result<Vec<u8>> {
+    // PBNode { Data = 1, Links = 2 }
+    let mut data = None;
+    for (number, field) in fields(node)? {
+        match (number, field) {
+            (1, Field::Bytes(bytes)) => data = Some(bytes),
+            (2, Field::Bytes(_)) => {
+                return Err(eyre!(
+                    "The file is split into multiple blocks, publish it as a single block"
+                ));
+            }
+            (number, _) => return Err(eyre!("Unexpected field {number} in dag-pb node")),
+        }
+    }
+    let data = data.ok_or_else(|| eyre!("The dag-pb node has no data"))?;
+
+    // UnixFS Data { Type = 1, Data = 2, filesize = 3, blocksizes = 4, ... }
+    let mut file_type = None;
+    let mut contents: &[u8] = &[];
+    for (number, field) in fields(data)? {
+        match (number, field) {
+            (1, Field::Varint(ty)) => file_type = Some(ty),
+            (2, Field::Bytes(bytes)) => contents = bytes,
+            _ => {}
+        }
+    }
+
+    // Raw = 0, File = 2
+    match file_type {
+        Some(0 | 2) => Ok(contents.to_vec()),
+        Some(ty) => Err(eyre!("UnixFS node of type {ty} is not a file")),
+        None => Err(eyre!("UnixFS node has no type")),
+    }
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Parse the fields of a protobuf message, just enough of the wire format for `dag-pb` and UnixFS
+fn fields(mut bytes: &[u8]) -> Result<Vec<(u64, Field<'_>)>> {
+    let mut fields = Vec::new();
+    while !bytes.is_empty() {
+        let key = read_varint(&mut bytes)?;
+        let field = match key & 0x7 {
+            0 => Field::Varint(read_varint(&mut bytes)?),
+            2 => {
+                let len = usize::try_from(read_varint(&mut bytes)?)?;
+                if len > bytes.len() {
+                    return Err(eyre!("Truncated protobuf field"));
+                }
+                let (field, rest) = bytes.split_at(len);
+                bytes = rest;
+                Field::Bytes(field)
+            }
+            wire_type => return Err(eyre!("Unsupported protobuf wire type {wire_type}")),
+        };
+        fields.push((key >> 3, field));
+    }
+
+    Ok(fields)
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[i + 1..];
+            return Ok(value);
+        }
+    }
+
+    Err(eyre!("Invalid protobuf varint"))
+}
@@ -0,0 +1,40 @@
+use super::NoArgs;
+use crate::HyperlaneContext;
+use crate::agent_config;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use sdk::extract::Context;
+use sdk::tangle::extract::TangleResult;
+use std::sync::Arc;
+
+pub const EFFECTIVE_CONFIG_JOB_ID: u8 = 7;
+
+/// Report the agent config the relayer runs with, as JSON
+///
+/// See [`agent_config::render`].
+pub async fn effective_config(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    _: NoArgs,
+) -> Result<TangleResult<String>> {
+    Ok(TangleResult(ctx.effective_config()?))
+}
+
+impl HyperlaneContext {
+    /// Render the agent config of the active config version
+    ///
+    /// # Errors
+    ///
+    /// * No configs have been applied
+    /// * Unable to read the applied configs
+    pub fn effective_config(&self) -> Result<String> {
+        let Some(version) = self.state.get().active_version else {
+            return Err(eyre!("No configs have been applied"));
+        };
+
+        agent_config::render(
+            &self.agent_configs(version)?,
+            &self.history.relay_chains(version)?,
+        )
+    }
+}
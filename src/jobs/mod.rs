@@ -0,0 +1,45 @@
+mod set_config;
+pub use set_config::*;
+mod stop_relayer;
+pub use stop_relayer::*;
+mod relayer_status;
+pub use relayer_status::*;
+mod rollback_config;
+pub use rollback_config::*;
+mod signer_balances;
+pub use signer_balances::*;
+mod rotate_signer;
+pub use rotate_signer::*;
+mod set_config_inline;
+pub use set_config_inline::*;
+mod effective_config;
+pub use effective_config::*;
+mod set_rpc_urls;
+pub use set_rpc_urls::*;
+
+use blueprint_sdk as sdk;
+use sdk::tangle::metadata::IntoTangleFieldTypes;
+use sdk::tangle_subxt::tangle_testnet_runtime::api::runtime_types::tangle_primitives::services::field::FieldType;
+use sdk::{FromJobCall, JobCall};
+use std::convert::Infallible;
+
+/// An extractor for jobs that take no arguments
+///
+/// Jobs with a [`Context`](sdk::extract::Context) need a final extractor describing their
+/// parameters, this describes an empty parameter list.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoArgs;
+
+impl<Ctx: Send + Sync> FromJobCall<Ctx> for NoArgs {
+    type Rejection = Infallible;
+
+    async fn from_job_call(_call: JobCall, _ctx: &Ctx) -> Result<Self, Self::Rejection> {
+        Ok(NoArgs)
+    }
+}
+
+impl IntoTangleFieldTypes for NoArgs {
+    fn into_tangle_fields() -> Vec<FieldType> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,110 @@
+use super::NoArgs;
+use crate::state::RelayerState;
+use crate::{HyperlaneContext, IMAGE};
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use docktopus::bollard::container::InspectContainerOptions;
+use sdk::extract::Context;
+use sdk::tangle::extract::TangleResult;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const RELAYER_STATUS_JOB_ID: u8 = 2;
+
+/// Whether the relayer is running
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// No configs have been applied yet
+    #[default]
+    NotStarted,
+    Running,
+    /// Stopped with the `stop_relayer` job
+    Stopped,
+    /// The relayer exited, and could not be brought back up
+    Crashed,
+}
+
+impl RunState {
+    /// The state of a relayer with the persisted `state`, given whether its container is `running`
+    pub fn new(running: bool, state: &RelayerState) -> Self {
+        if running {
+            Self::Running
+        } else if state.stopped {
+            Self::Stopped
+        } else if state.active_version.is_some() {
+            Self::Crashed
+        } else {
+            Self::NotStarted
+        }
+    }
+}
+
+/// The result of the `relayer_status` job
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayerStatus {
+    pub state: RunState,
+    /// Seconds since the relayer container was started, `0` if it isn't running
+    pub uptime_secs: u64,
+    /// Number of times the relayer was restarted after crashing
+    pub restart_count: u64,
+    /// The active config version, `0` if no configs were applied
+    pub config_version: u64,
+    /// The hash of the applied configs, see [`HyperlaneContext::config_hash`]
+    pub config_hash: String,
+    /// The relayer image
+    pub image: String,
+    /// The comma-separated list of relay chains
+    pub relay_chains: String,
+}
+
+/// Report the status of the relayer
+pub async fn relayer_status(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    _: NoArgs,
+) -> Result<TangleResult<RelayerStatus>> {
+    Ok(TangleResult(ctx.status().await?))
+}
+
+impl HyperlaneContext {
+    /// Get the current [`RelayerStatus`]
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the applied configs
+    pub async fn status(&self) -> Result<RelayerStatus> {
+        let state = self.state.get();
+        let container_id = self.container.lock().await.clone();
+
+        let running = match &container_id {
+            Some(container_id) => self
+                .connection
+                .inspect_container(container_id, None::<InspectContainerOptions>)
+                .await
+                .ok()
+                .and_then(|inspect| inspect.state)
+                .and_then(|state| state.running)
+                .unwrap_or(false),
+            None => false,
+        };
+
+        let relay_chains = match state.active_version {
+            Some(version) => std::fs::read_to_string(self.history.relay_chains_path(version))?,
+            None => String::new(),
+        };
+
+        Ok(RelayerStatus {
+            state: RunState::new(running, &state),
+            uptime_secs: if running {
+                state.uptime_secs().unwrap_or_default()
+            } else {
+                0
+            },
+            restart_count: self.restart_count(),
+            config_version: state.active_version.unwrap_or_default(),
+            config_hash: self.config_hash()?.to_string(),
+            image: IMAGE.to_string(),
+            relay_chains,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use crate::HyperlaneContext;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use sdk::extract::Context;
+use sdk::tangle::extract::{TangleArg, TangleResult};
+use std::sync::Arc;
+
+pub const ROLLBACK_CONFIG_JOB_ID: u8 = 3;
+
+/// Restart the relayer with a previously applied config version
+///
+/// If the relayer fails to start, the config version that was active before is restored.
+pub async fn rollback_config(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    TangleArg(version): TangleArg<u64>,
+) -> Result<TangleResult<u64>> {
+    if !ctx.history.contains(version) {
+        return Err(eyre!(
+            "Config version {version} does not exist, or has been pruned"
+        ));
+    }
+
+    let _lifecycle = ctx.lifecycle.lock().await;
+
+    sdk::info!("Rolling back to config version {version}");
+    ctx.apply_version(version).await?;
+
+    Ok(TangleResult(0))
+}
@@ -0,0 +1,182 @@
+use crate::HyperlaneContext;
+use crate::agent_config::{AgentConfig, Protocol};
+use crate::balances::{self, RpcHttpClient};
+use crate::signer::{HexKey, Signers};
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use sdk::alloy::primitives::Address;
+use sdk::extract::Context;
+use sdk::tangle::extract::{Optional, TangleArgs2, TangleResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const ROTATE_SIGNER_JOB_ID: u8 = 5;
+
+/// How long a sweep has to be confirmed on each chain
+const SWEEP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The result of the `rotate_signer` job
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotatedSigner {
+    /// The address of the new default signer
+    pub address: String,
+    /// The comma-separated list of relay chains funds were swept on
+    pub swept: String,
+    /// The comma-separated list of relay chains the sweep failed on, see the operator's logs
+    pub sweep_failed: String,
+}
+
+/// Switch the relayer to a different ECDSA key from the keystore
+///
+/// The key with EVM address `address` is used, or a new key is generated if none is given. With
+/// `sweep`, the old signer's funds are transferred to the new one on every EVM relay chain.
+pub async fn rotate_signer(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    TangleArgs2(Optional(address), sweep): TangleArgs2<Optional<String>, bool>,
+) -> Result<TangleResult<RotatedSigner>> {
+    let address = address
+        .map(|address| {
+            address
+                .parse::<Address>()
+                .map_err(|e| eyre!("Invalid signer address `{address}`: {e}"))
+        })
+        .transpose()?;
+
+    Ok(TangleResult(ctx.rotate_signer(address, sweep).await?))
+}
+
+impl HyperlaneContext {
+    /// Switch the relayer to the keystore's ECDSA key with `address`, or a newly generated one
+    ///
+    /// A running relayer is restarted with the new key, and the old key is restored if it fails
+    /// to start. With `sweep`, the old signer's funds are then transferred to the new one on every
+    /// EVM relay chain with `rpcUrls`.
+    ///
+    /// # Errors
+    ///
+    /// * There is no ECDSA key with `address` in the keystore
+    /// * The relayer already signs with the key
+    /// * The relayer failed to start with the new key
+    pub async fn rotate_signer(
+        &self,
+        address: Option<Address>,
+        sweep: bool,
+    ) -> Result<RotatedSigner> {
+        let lifecycle = self.lifecycle.lock().await;
+
+        let state = self.state.get();
+        let keystore = self.env.keystore();
+        let old_key = HexKey::from_keystore(&keystore, state.signer)?;
+        let new_key = match address {
+            Some(address) => HexKey::from_keystore(&keystore, Some(address))?,
+            None => HexKey::generate(&keystore)?,
+        };
+        let new_address = new_key.address()?;
+        if new_address == old_key.address()? {
+            return Err(eyre!("The relayer already signs with {new_address}"));
+        }
+
+        // Needed to sweep the old signer's funds once it has been replaced
+        let old = match state.active_version {
+            Some(version) => {
+                let agent_config = self.agent_config(version)?;
+                let relay_chains = self.history.relay_chains(version)?;
+                let signers = self.signers(&agent_config, &relay_chains)?;
+                Some((agent_config, relay_chains, signers))
+            }
+            None => None,
+        };
+
+        sdk::info!("Rotating the relayer's signer to {new_address}");
+        self.remove_existing_container().await?;
+        self.state
+            .update(|state| state.signer = Some(new_address))?;
+
+        if state.active_version.is_some()
+            && !state.stopped
+            && let Err(e) = self.spinup_container().await
+        {
+            sdk::error!("{e}");
+            sdk::warn!("Restoring the previous signer");
+            self.remove_existing_container().await?;
+            self.state.update(|current| current.signer = state.signer)?;
+            self.spinup_container().await?;
+            return Err(e.wrap_err("Relayer failed to start with the new signer, reverted"));
+        }
+
+        // The old signer is no longer in use, so its funds can be moved without the lock
+        drop(lifecycle);
+
+        let mut result = RotatedSigner {
+            address: new_address.to_string(),
+            ..Default::default()
+        };
+        if sweep && let Some((agent_config, relay_chains, old_signers)) = &old {
+            let new_signers = self.signers(agent_config, relay_chains)?;
+            let (swept, failed) = sweep_signers(
+                &self.rpc_client,
+                agent_config,
+                relay_chains,
+                old_signers,
+                &new_signers,
+            )
+            .await?;
+            result.swept = swept.join(",");
+            result.sweep_failed = failed.join(",");
+        }
+
+        Ok(result)
+    }
+}
+
+/// Sweep the funds of `from` to `to` on every EVM relay chain with `rpcUrls`
+///
+/// Returns the chains funds were swept on, and the chains the sweep failed on.
+async fn sweep_signers<'a>(
+    client: &'a RpcHttpClient,
+    agent_config: &'a AgentConfig,
+    relay_chains: &'a [String],
+    from: &'a Signers,
+    to: &'a Signers,
+) -> Result<(Vec<&'a str>, Vec<&'a str>)> {
+    let mut sweeps = Vec::new();
+    for chain in relay_chains {
+        if agent_config.protocol(chain) != Some(Protocol::Ethereum) {
+            continue;
+        }
+        let Some(rpc_urls) = agent_config
+            .chains
+            .get(chain)
+            .and_then(|config| config.rpc_urls.as_deref())
+        else {
+            continue;
+        };
+
+        let from = from.for_evm_chain(chain);
+        let to = to.for_evm_chain(chain).address()?;
+        sweeps.push(async move {
+            let sweep = balances::sweep(client, rpc_urls, from, to, SWEEP_TIMEOUT).await;
+            (chain.as_str(), sweep)
+        });
+    }
+
+    let mut swept = Vec::new();
+    let mut failed = Vec::new();
+    for (chain, sweep) in futures::future::join_all(sweeps).await {
+        match sweep {
+            Ok(Some(tx_hash)) => {
+                sdk::info!("Swept the old signer's funds on `{chain}` in {tx_hash}");
+                swept.push(chain);
+            }
+            Ok(None) => sdk::info!("The old signer has no funds to sweep on `{chain}`"),
+            Err(e) => {
+                sdk::error!("Failed to sweep the old signer's funds on `{chain}`: {e}");
+                failed.push(chain);
+            }
+        }
+    }
+
+    Ok((swept, failed))
+}
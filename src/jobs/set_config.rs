@@ -0,0 +1,88 @@
+use crate::HyperlaneContext;
+use crate::agent_config;
+use crate::fetch::ConfigUrl;
+use crate::history::{ConfigSource, Origin};
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::WrapErr;
+use sdk::extract::Context;
+use sdk::tangle::extract::{CallId, Caller, List, Optional, TangleArgs2, TangleResult};
+use std::sync::Arc;
+
+pub const SET_CONFIG_JOB_ID: u8 = 0;
+
+pub async fn set_config(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs2(Optional(config_urls), relay_chains): TangleArgs2<Optional<List<String>>, String>,
+) -> Result<TangleResult<u64>> {
+    let config_urls = config_urls.map(|List(urls)| urls).unwrap_or_default();
+    let parsed = config_urls
+        .iter()
+        .map(|url| url.parse::<ConfigUrl>())
+        .collect::<Result<Vec<_>>>()?;
+    // Configs are only built from registries for the relay chains
+    let chains = if parsed.iter().any(|url| url.registry) {
+        agent_config::parse_relay_chains(&relay_chains)
+            .wrap_err("Registry config URLs need `relay_chains`")?
+    } else {
+        Vec::new()
+    };
+    let fetched = ctx.fetcher.fetch_all(&parsed, &chains).await?;
+
+    let sources = config_urls
+        .into_iter()
+        .zip(&parsed)
+        .zip(&fetched)
+        .map(|((url, parsed), fetched)| {
+            ConfigSource::new(
+                Some(url),
+                fetched.hash,
+                parsed.pin.is_some(),
+                &fetched.config,
+            )
+        })
+        .collect();
+    let configs = fetched
+        .into_iter()
+        .map(|fetched| fetched.config)
+        .collect::<Vec<_>>();
+
+    let origin = Origin {
+        call_id: Some(call_id),
+        caller: Some(caller.to_string()),
+        sources,
+    };
+    ctx.apply_configs(&configs, &relay_chains, origin).await?;
+
+    Ok(TangleResult(0))
+}
+
+impl HyperlaneContext {
+    /// Store `configs` and `relay_chains` as a new config version, and start the relayer with it
+    ///
+    /// The configs are validated before the running relayer is touched.
+    ///
+    /// # Errors
+    ///
+    /// * The configs or relay chains are invalid
+    /// * The relayer failed to start with the new version, see [`Self::apply_version`]
+    pub(crate) async fn apply_configs(
+        &self,
+        configs: &[String],
+        relay_chains: &str,
+        origin: Origin,
+    ) -> Result<()> {
+        // Reject bad configs before touching the running relayer
+        let agent_config = agent_config::validate(configs)?;
+        let relay_chains = agent_config::parse_relay_chains(relay_chains)?;
+        agent_config.check_relay_chains(&relay_chains)?;
+        let relay_chains = relay_chains.join(",");
+
+        let _lifecycle = self.lifecycle.lock().await;
+
+        let version = self.history.create(configs, &relay_chains, origin)?;
+        self.apply_version(version).await
+    }
+}
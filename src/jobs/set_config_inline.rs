@@ -0,0 +1,36 @@
+use crate::HyperlaneContext;
+use crate::config_format;
+use crate::fetch::ContentHash;
+use crate::history::{ConfigSource, Origin};
+use crate::inline_config;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use sdk::extract::Context;
+use sdk::tangle::extract::{CallId, Caller, List, TangleArgs2, TangleResult};
+use std::sync::Arc;
+
+pub const SET_CONFIG_INLINE_JOB_ID: u8 = 6;
+
+/// Start the relayer with an agent config passed as bytes, instead of a URL like `set_config`
+///
+/// The config may be JSON or YAML, and gzip compressed, see [`inline_config::decode`].
+pub async fn set_config_inline(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs2(List(config), relay_chains): TangleArgs2<List<u8>, String>,
+) -> Result<TangleResult<u64>> {
+    // The hash of the payload as submitted, which anyone can check against the job call
+    let hash = ContentHash::keccak256(&config);
+    let config = inline_config::decode(config, ctx.settings.max_inline_config)?;
+    let config = config_format::to_json(config, None)?;
+
+    let origin = Origin {
+        call_id: Some(call_id),
+        caller: Some(caller.to_string()),
+        sources: vec![ConfigSource::new(None, Some(hash), false, &config)],
+    };
+    ctx.apply_configs(&[config], &relay_chains, origin).await?;
+
+    Ok(TangleResult(0))
+}
@@ -0,0 +1,159 @@
+use crate::HyperlaneContext;
+use crate::agent_config;
+use crate::history::{ConfigHistory, ConfigSource, Origin};
+use crate::rpc_urls::{self, RpcConsensusType};
+use crate::state::StateStore;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use sdk::extract::Context;
+use sdk::tangle::extract::{CallId, Caller, List, Optional, TangleArgs2, TangleResult};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub const SET_RPC_URLS_JOB_ID: u8 = 8;
+
+/// Override the RPCs of the relay chains, and restart the relayer with them
+///
+/// A stopped relayer stays stopped.
+///
+/// `rpc_urls` are `<chain>=<url>` entries, see [`rpc_urls::parse_rpc_urls`]. Returns the new
+/// config version.
+pub async fn set_rpc_urls(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs2(List(rpc_urls), Optional(consensus_type)): TangleArgs2<
+        List<String>,
+        Optional<String>,
+    >,
+) -> Result<TangleResult<u64>> {
+    let rpc_urls = rpc_urls::parse_rpc_urls(&rpc_urls)?;
+    let consensus_type = consensus_type
+        .map(|consensus_type| consensus_type.parse::<RpcConsensusType>())
+        .transpose()?;
+
+    let origin = Origin {
+        call_id: Some(call_id),
+        caller: Some(caller.to_string()),
+        sources: Vec::new(),
+    };
+    let version = ctx
+        .apply_rpc_urls(&rpc_urls, consensus_type, origin)
+        .await?;
+
+    Ok(TangleResult(version))
+}
+
+impl HyperlaneContext {
+    /// Store the active configs with a new set of RPC overrides as a new config version, and
+    /// restart the relayer with it
+    ///
+    /// See [`store_rpc_urls`]. A stopped relayer is left stopped, with the new version active.
+    ///
+    /// # Errors
+    ///
+    /// * The overrides couldn't be stored, see [`store_rpc_urls`]
+    /// * The relayer failed to start with the new version, see [`Self::apply_version`]
+    pub(crate) async fn apply_rpc_urls(
+        &self,
+        rpc_urls: &BTreeMap<String, Vec<String>>,
+        consensus_type: Option<RpcConsensusType>,
+        origin: Origin,
+    ) -> Result<u64> {
+        let _lifecycle = self.lifecycle.lock().await;
+
+        let version = store_rpc_urls(&self.history, &self.state, rpc_urls, consensus_type, origin)?;
+        if !self.state.get().stopped {
+            self.apply_version(version).await?;
+        }
+
+        Ok(version)
+    }
+}
+
+/// Store the active configs with a new set of RPC overrides as a new config version
+///
+/// The overrides replace any set before. Without any URLs or `consensus_type`, the new version
+/// has no overrides.
+///
+/// If the relayer is stopped, the new version is activated right away, and left for the job that
+/// starts the relayer again, like with `rotate_signer`. Otherwise, it's up to the caller to switch
+/// the relayer over to it.
+///
+/// # Errors
+///
+/// * No configs have been applied
+/// * Any of the chains aren't relay chains
+/// * The configs are invalid with the overrides
+/// * Unable to store the new version
+pub fn store_rpc_urls(
+    history: &ConfigHistory,
+    state: &StateStore,
+    rpc_urls: &BTreeMap<String, Vec<String>>,
+    consensus_type: Option<RpcConsensusType>,
+    mut origin: Origin,
+) -> Result<u64> {
+    let Some(active) = state.get().active_version else {
+        return Err(eyre!(
+            "No configs have been applied, use `set_config` first"
+        ));
+    };
+
+    let relay_chains = history.relay_chains(active)?;
+    let unrelayed = rpc_urls
+        .keys()
+        .filter(|chain| !relay_chains.contains(chain))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !unrelayed.is_empty() {
+        return Err(eyre!(
+            "RPC URLs were given for chains that aren't relayed: {}",
+            unrelayed.join(", ")
+        ));
+    }
+
+    // The active configs, without the overrides they were applied with
+    let rpc_urls_path = history.rpc_urls_path(active);
+    let configs = history
+        .config_files(active)?
+        .into_iter()
+        .filter(|path| *path != rpc_urls_path)
+        .map(std::fs::read_to_string)
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let overlay = if rpc_urls.is_empty() && consensus_type.is_none() {
+        None
+    } else {
+        Some(rpc_urls::overlay(rpc_urls, consensus_type)?)
+    };
+
+    // Reject bad overrides before touching the running relayer
+    let mut merged = configs.clone();
+    merged.extend(overlay.clone());
+    agent_config::validate(&merged)?;
+
+    // Configs imported from before sources were recorded have none
+    let mut sources = history.meta(active)?.sources;
+    sources.truncate(configs.len());
+    if let Some(overlay) = &overlay
+        && sources.len() == configs.len()
+    {
+        sources.push(ConfigSource::new(None, None, false, overlay));
+    }
+    origin.sources = sources;
+
+    let version = history.create_with_rpc_urls(
+        &configs,
+        overlay.as_deref(),
+        &relay_chains.join(","),
+        origin,
+    )?;
+    if state.get().stopped {
+        state.update(|state| state.activate(version))?;
+        history.prune(&[version, active])?;
+        sdk::info!("Relayer is stopped, config version {version} was stored without starting it");
+    }
+
+    Ok(version)
+}
@@ -0,0 +1,103 @@
+use super::NoArgs;
+use crate::HyperlaneContext;
+use crate::agent_config::Protocol;
+use crate::balances;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use sdk::extract::Context;
+use sdk::tangle::extract::{List, TangleResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub const SIGNER_BALANCES_JOB_ID: u8 = 4;
+
+/// The error reported for a balance that couldn't be queried, the details are logged
+const QUERY_FAILED: &str = "Balance query failed, see the operator's logs";
+
+/// The balance of the relayer's signer on a chain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerBalance {
+    pub chain: String,
+    /// The address of the signer on the chain
+    pub address: String,
+    /// The balance in the smallest unit of the native token, empty if it couldn't be queried
+    pub balance: String,
+    /// The balance below which the signer is low on funds
+    pub min_balance: String,
+    /// Whether the balance is below `min_balance`
+    pub low: bool,
+    /// Empty if the balance was queried, otherwise a generic error, the details are only logged
+    pub error: String,
+}
+
+/// Report the relayer's signer balances on every chain in the active agent config
+pub async fn signer_balances(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    _: NoArgs,
+) -> Result<TangleResult<List<SignerBalance>>> {
+    Ok(TangleResult(List(ctx.signer_balances().await?)))
+}
+
+impl HyperlaneContext {
+    /// Query the signer balances on every EVM chain with `rpcUrls` in the active agent config
+    ///
+    /// Chains whose balance couldn't be queried are reported with an `error`. Nothing is reported
+    /// if no configs were applied.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the applied configs
+    /// * Unable to load the signer keys
+    pub async fn signer_balances(&self) -> Result<Vec<SignerBalance>> {
+        let Some(version) = self.active_config_version() else {
+            return Ok(Vec::new());
+        };
+
+        let agent_config = self.agent_config(version)?;
+        let signers = self.signers(&agent_config, &self.history.relay_chains(version)?)?;
+
+        let settings = &self.settings.balances;
+        let mut queries = Vec::new();
+        for (chain, config) in &agent_config.chains {
+            if agent_config.protocol(chain) != Some(Protocol::Ethereum) {
+                continue;
+            }
+            let Some(rpc_urls) = config.rpc_urls.as_deref() else {
+                continue;
+            };
+
+            let address = signers.for_evm_chain(chain).address()?;
+            let min_balance = settings.min_balance(chain);
+
+            queries.push(async move {
+                let balance = balances::query_balance(
+                    &self.rpc_client,
+                    rpc_urls,
+                    address,
+                    settings.rpc_timeout,
+                )
+                .await;
+                if let Err(e) = &balance {
+                    sdk::warn!("Failed to check the balance of signer {address} on `{chain}`: {e}");
+                }
+                SignerBalance {
+                    chain: chain.clone(),
+                    address: address.to_string(),
+                    balance: balance
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    min_balance: min_balance.to_string(),
+                    low: balance.as_ref().is_ok_and(|balance| *balance < min_balance),
+                    // The details may include the RPC's responses, which aren't for the caller
+                    error: balance
+                        .err()
+                        .map(|_| String::from(QUERY_FAILED))
+                        .unwrap_or_default(),
+                }
+            });
+        }
+
+        Ok(futures::future::join_all(queries).await)
+    }
+}
@@ -0,0 +1,28 @@
+use super::NoArgs;
+use crate::HyperlaneContext;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use sdk::extract::Context;
+use sdk::tangle::extract::TangleResult;
+use std::sync::Arc;
+
+pub const STOP_RELAYER_JOB_ID: u8 = 1;
+
+/// Stop the relayer
+///
+/// The relayer stays stopped, including across restarts of the blueprint, until a job applies a
+/// config version: `set_config`, `set_config_inline` or `rollback_config`. `rotate_signer` and
+/// `set_rpc_urls` leave it stopped.
+pub async fn stop_relayer(
+    Context(ctx): Context<Arc<HyperlaneContext>>,
+    _: NoArgs,
+) -> Result<TangleResult<u64>> {
+    let _lifecycle = ctx.lifecycle.lock().await;
+
+    // Recorded first, so a crash while removing the container doesn't bring it back on startup
+    ctx.state.update(|state| state.stopped = true)?;
+    ctx.remove_existing_container().await?;
+
+    sdk::info!("Relayer stopped");
+    Ok(TangleResult(0))
+}
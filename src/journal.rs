@@ -0,0 +1,141 @@
+//! A journal of config applies in progress
+//!
+//! Before the relayer is switched to a new config version, the state to restore is recorded in
+//! the journal. The entry is cleared once the apply either succeeds or is reverted, so an entry
+//! found on startup means the blueprint died part way through, or the revert failed, and the
+//! apply is rolled back.
+
+use crate::history::{sync_dir, write_synced};
+use crate::state::StateStore;
+use blueprint_sdk as sdk;
+use color_eyre::{Report, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// An apply in progress
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApplyIntent {
+    /// The version being applied
+    pub version: u64,
+    /// The active version before the apply
+    pub active_version: Option<u64>,
+    /// The fallback version before the apply
+    pub previous_version: Option<u64>,
+    /// Whether the relayer was stopped before the apply
+    pub stopped: bool,
+}
+
+/// Why an apply didn't go through
+#[derive(Debug)]
+pub enum ApplyError {
+    /// The apply failed, and was reverted
+    Reverted(Report),
+    /// The apply failed part way, and couldn't be reverted
+    Interrupted(Report),
+}
+
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Record the start of an apply
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the journal
+    pub fn begin(&self, intent: &ApplyIntent) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        write_synced(&tmp, &serde_json::to_vec(intent)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        if let Some(parent) = self.path.parent() {
+            sync_dir(parent)?;
+        }
+        Ok(())
+    }
+
+    /// The apply that was interrupted, if any
+    ///
+    /// # Errors
+    ///
+    /// * The journal exists, but can't be read
+    pub fn pending(&self) -> Result<Option<ApplyIntent>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let intent = std::fs::read(&self.path)?;
+        Ok(Some(serde_json::from_slice(&intent)?))
+    }
+
+    /// Mark the apply as finished
+    ///
+    /// # Errors
+    ///
+    /// * Unable to remove the journal
+    pub fn finish(&self) -> Result<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    /// Run `apply`, recorded as `intent`
+    ///
+    /// The entry is cleared once `apply` succeeds, or fails and is reverted. If it couldn't be
+    /// reverted, the entry is kept, for [`Self::roll_back`] to restore the state on the next startup.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the journal
+    /// * `apply` failed
+    pub async fn run(
+        &self,
+        intent: &ApplyIntent,
+        apply: impl Future<Output = std::result::Result<(), ApplyError>>,
+    ) -> Result<()> {
+        self.begin(intent)?;
+        match apply.await {
+            Ok(()) => self.finish(),
+            Err(ApplyError::Reverted(e)) => {
+                self.finish()?;
+                Err(e)
+            }
+            Err(ApplyError::Interrupted(e)) => Err(e.wrap_err(format!(
+                "Applying config version {} failed, it will be rolled back on the next startup",
+                intent.version
+            ))),
+        }
+    }
+
+    /// Restore the state from before an interrupted apply, if there is one
+    ///
+    /// Returns the interrupted apply.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the journal, or to restore the state
+    pub fn roll_back(&self, state: &StateStore) -> Result<Option<ApplyIntent>> {
+        let Some(intent) = self.pending()? else {
+            return Ok(None);
+        };
+
+        sdk::warn!(
+            "Applying config version {} was interrupted, rolling back",
+            intent.version
+        );
+        state.update(|state| {
+            state.active_version = intent.active_version;
+            state.previous_version = intent.previous_version;
+            state.stopped = intent.stopped;
+        })?;
+
+        self.finish()?;
+        Ok(Some(intent))
+    }
+}
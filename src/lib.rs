@@ -1,23 +1,56 @@
+pub mod agent_config;
+pub mod balances;
+pub mod config_format;
+pub mod fetch;
+pub mod history;
+pub mod inline_config;
+pub mod ipfs;
+pub mod jobs;
+pub mod journal;
+pub mod local_config;
+pub mod ownership;
+pub mod readiness;
+pub mod registry;
+pub mod rpc_urls;
+pub mod settings;
+pub mod signer;
+pub mod state;
+pub mod supervisor;
+
+use agent_config::{AgentConfig, Protocol};
+use balances::RpcHttpClient;
 use blueprint_sdk as sdk;
 use color_eyre::Result;
 use color_eyre::eyre::eyre;
 use docktopus::DockerBuilder;
+use docktopus::bollard::container::{
+    Config, CreateContainerOptions, InspectContainerOptions, RemoveContainerOptions,
+    StartContainerOptions,
+};
+use docktopus::bollard::models::{ContainerCreateResponse, HostConfig};
 use docktopus::bollard::network::ConnectNetworkOptions;
 use docktopus::container::Container;
-use sdk::alloy::hex;
+use fetch::ConfigFetcher;
+use history::ConfigHistory;
+use journal::{ApplyError, ApplyIntent, Journal};
+use ownership::Owner;
+use readiness::ReadySignal;
+use sdk::alloy::primitives::B256;
 use sdk::crypto::sp_core::SpEcdsa;
 use sdk::crypto::tangle_pair_signer::TanglePairSigner;
-use sdk::extract::Context;
 use sdk::keystore::backends::Backend;
 use sdk::macros::context::{ServicesContext, TangleClientContext};
 use sdk::runner::config::BlueprintEnvironment;
-use sdk::tangle::extract::TangleResult;
-use sdk::tangle::extract::{List, Optional, TangleArgs2};
-use sdk::tokio;
 use sdk::tokio::sync::Mutex;
+use settings::Settings;
+use signer::{ChainSigner, RelayChain, Signers};
+use state::StateStore;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub use jobs::*;
 
 pub fn default_data_dir() -> PathBuf {
     const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
@@ -29,29 +62,215 @@ pub struct HyperlaneContext {
     #[config]
     pub env: BlueprintEnvironment,
     data_dir: PathBuf,
+    settings: Settings,
+    state: Arc<StateStore>,
+    history: ConfigHistory,
+    journal: Journal,
+    fetcher: ConfigFetcher,
+    rpc_client: RpcHttpClient,
     connection: Arc<DockerBuilder>,
+    /// Only locked to read or swap the handle, changes to the container itself hold `lifecycle`
     container: Arc<Mutex<Option<String>>>,
+    /// Held for the duration of any sequence of container changes (jobs, supervisor restarts)
+    lifecycle: Arc<Mutex<()>>,
+    restarts: Arc<AtomicU64>,
 }
 
-const IMAGE: &str = "gcr.io/abacus-labs-dev/hyperlane-agent:agents-v1.2.0";
+/// The relayer image, matching the bundled [default chains](registry::SNAPSHOT_VERSION)
+pub const IMAGE: &str = "gcr.io/abacus-labs-dev/hyperlane-agent:agents-v1.2.0";
+
 impl HyperlaneContext {
     pub async fn new(env: BlueprintEnvironment, data_dir: PathBuf) -> Result<Self> {
+        Self::with_settings(env, data_dir, Settings::default()).await
+    }
+
+    pub async fn with_settings(
+        env: BlueprintEnvironment,
+        data_dir: PathBuf,
+        settings: Settings,
+    ) -> Result<Self> {
         let connection = DockerBuilder::new().await?;
-        Ok(Self {
+        let state = StateStore::open(data_dir.join("state.json"))?;
+        let history = ConfigHistory::new(data_dir.join("configs"), settings.config_history);
+        if state.get().active_version.is_none()
+            && let Some(version) = history.import_legacy(&data_dir)?
+        {
+            state.update(|state| state.activate(version))?;
+        }
+
+        let journal = Journal::new(data_dir.join("apply.journal"));
+        let fetcher = ConfigFetcher::new(settings.fetch.clone(), env.test_mode)?;
+        let rpc_client = RpcHttpClient::new(settings.fetch.allow_private_ips)?;
+
+        let ctx = Self {
             env,
             data_dir,
+            settings,
+            state: Arc::new(state),
+            history,
+            journal,
+            fetcher,
+            rpc_client,
             connection: Arc::new(connection),
             container: Arc::new(Mutex::new(None)),
+            lifecycle: Arc::new(Mutex::new(())),
+            restarts: Arc::new(AtomicU64::new(0)),
+        };
+
+        ctx.recover_interrupted_apply()?;
+        ctx.reconcile().await?;
+        sdk::tokio::spawn(supervisor::supervise(ctx.clone()));
+        sdk::tokio::spawn(ownership::collect_garbage_periodically(
+            ctx.clone(),
+            ctx.settings.gc_interval,
+        ));
+        sdk::tokio::spawn(balances::monitor(ctx.clone()));
+
+        Ok(ctx)
+    }
+
+    /// Pick up where a previous run of the blueprint left off
+    ///
+    /// If the container we last started is still running, it is adopted. Otherwise, it is removed,
+    /// and a fresh relayer is started from the last applied configs (if any). Any other containers
+    /// carrying our labels are then removed.
+    async fn reconcile(&self) -> Result<()> {
+        let _lifecycle = self.lifecycle.lock().await;
+        self.adopt_or_restart().await?;
+        ownership::collect_garbage(self).await
+    }
+
+    async fn adopt_or_restart(&self) -> Result<()> {
+        let state = self.state.get();
+        if let Some(container_id) = state.container_id.clone() {
+            if state.should_run() && self.is_adoptable(&container_id).await {
+                sdk::info!("Adopting running relayer container `{container_id}`");
+                *self.container.lock().await = Some(container_id);
+                return Ok(());
+            }
+
+            sdk::warn!("Removing stale relayer container `{container_id}`");
+            *self.container.lock().await = Some(container_id);
+            if let Err(e) = self.remove_existing_container().await {
+                sdk::warn!("Failed to remove stale relayer container: {e}");
+                self.state.update(state::RelayerState::clear_container)?;
+            }
+        }
+
+        if !state.should_run() {
+            if state.stopped {
+                sdk::info!("Relayer was stopped, waiting for a job to apply configs");
+            }
+            // Otherwise, no config was ever applied, wait for a `set_config` job
+            return Ok(());
+        }
+
+        sdk::info!("Starting relayer from the last applied configs");
+        if let Err(e) = self.spinup_container().await {
+            sdk::error!("Failed to start relayer from the last applied configs: {e}");
+            self.remove_existing_container().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the container is a running relayer using our database
+    async fn is_adoptable(&self, container_id: &str) -> bool {
+        let Ok(inspect) = self
+            .connection
+            .inspect_container(container_id, None::<InspectContainerOptions>)
+            .await
+        else {
+            return false;
+        };
+
+        let running = inspect
+            .state
+            .and_then(|state| state.running)
+            .unwrap_or(false);
+        let Some(config) = inspect.config else {
+            return false;
+        };
+        let image_matches = config.image.is_some_and(|image| image == IMAGE);
+        let labels = config.labels.unwrap_or_default();
+        let owned = self.owner().is_ok_and(|owner| owner.owns(&labels));
+        // Not the case if an apply was interrupted and rolled back
+        let version_matches = self
+            .state
+            .get()
+            .active_version
+            .map(|version| version.to_string())
+            .as_ref()
+            == labels.get(ownership::CONFIG_VERSION_LABEL);
+        let db_path = self.hyperlane_db_path();
+        let uses_our_db = inspect.mounts.unwrap_or_default().iter().any(|mount| {
+            mount.destination.as_deref() == Some("/hyperlane_db")
+                && mount.source.as_deref().map(Path::new) == Some(db_path.as_path())
+        });
+
+        running && image_matches && owned && version_matches && uses_our_db
+    }
+
+    /// The number of times the supervisor has restarted a crashed relayer
+    pub fn restart_count(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// The blueprint and service this instance of the blueprint is running for
+    ///
+    /// # Errors
+    ///
+    /// * The blueprint isn't running on Tangle
+    pub fn owner(&self) -> Result<Owner> {
+        let settings = self.env.protocol_settings.tangle()?;
+        Ok(Owner {
+            blueprint_id: settings.blueprint_id,
+            service_id: settings.service_id,
         })
     }
 
+    /// A hash over the applied agent configs and relay chains, zero if none were applied
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the applied configs
+    pub fn config_hash(&self) -> Result<B256> {
+        match self.state.get().active_version {
+            Some(version) => history::config_hash(
+                &self.history.agent_configs_path(version),
+                &self.history.relay_chains_path(version),
+            ),
+            None => Ok(B256::ZERO),
+        }
+    }
+
+    /// The ID of the relayer container, if one is running
+    pub async fn container_id(&self) -> Option<String> {
+        self.container.lock().await.clone()
+    }
+
+    /// The config version the relayer runs with, if any configs were applied
+    pub fn active_config_version(&self) -> Option<u64> {
+        self.state.get().active_version
+    }
+
+    async fn is_tracked(&self, container_id: &str) -> bool {
+        self.container.lock().await.as_deref() == Some(container_id)
+    }
+
+    /// Start a relayer with the active config version, unless one is running
+    ///
+    /// The caller must hold the `lifecycle` lock.
     async fn spinup_container(&self) -> Result<()> {
-        let mut container_guard = self.container.lock().await;
-        if container_guard.is_some() {
+        if self.container.lock().await.is_some() {
             return Ok(());
         }
 
-        sdk::info!("Spinning up new container");
+        let Some(version) = self.state.get().active_version else {
+            return Err(eyre!("No configs have been applied"));
+        };
+
+        sdk::info!("Spinning up new container with config version {version}");
 
         // TODO: Bollard isn't pulling the image for some reason?
         let output = Command::new("docker").args(["pull", IMAGE]).output()?;
@@ -59,15 +278,24 @@ impl HyperlaneContext {
             return Err(eyre!("Docker pull failed"));
         }
 
-        let mut container = Container::new(self.connection.client(), IMAGE);
-
         let keystore = self.env.keystore();
         let ecdsa_pub = keystore.first_local::<SpEcdsa>()?;
         let ecdsa_pair = keystore.get_secret::<SpEcdsa>(&ecdsa_pub)?;
         let tangle_ecdsa_pair = TanglePairSigner::new(ecdsa_pair.0);
 
-        let alloy_key = tangle_ecdsa_pair.alloy_key()?;
-        let secret = hex::encode(alloy_key.to_bytes());
+        let relay_chains = self.history.relay_chains(version)?;
+        let config_files = self.history.config_files(version)?;
+
+        // Kept until the relayer is ready, after which it has read its config
+        let signer_config = {
+            let signers = self.signers(&self.agent_config(version)?, &relay_chains)?;
+            for (chain, signer) in &signers.chains {
+                if let ChainSigner::Ethereum(key) = signer {
+                    sdk::info!("Signer for `{chain}`: {}", key.address()?);
+                }
+            }
+            signer::write_signer_config(&self.secrets_dir(), &signers)?
+        };
 
         let hyperlane_db_path = self.hyperlane_db_path();
         if !hyperlane_db_path.exists() {
@@ -78,212 +306,271 @@ impl HyperlaneContext {
 
         let mut binds = vec![format!("{}:/hyperlane_db", hyperlane_db_path.display())];
 
-        let agent_configs_path = self.agent_configs_path();
-        let agent_configs_path_exists = agent_configs_path.exists();
-        if agent_configs_path_exists {
+        let agent_configs_path = self.history.agent_configs_path(version);
+        if agent_configs_path.exists() {
             binds.push(format!(
                 "{}:/config:ro",
                 agent_configs_path.to_string_lossy()
             ));
         }
 
-        let mut env = Vec::new();
+        binds.push(format!(
+            "{}:{}:ro",
+            signer_config.path().display(),
+            signer::signer_config_mount()
+        ));
 
-        if agent_configs_path_exists {
-            let mut config_files = Vec::new();
-
-            let files = std::fs::read_dir(agent_configs_path)?;
-            for config in files {
-                let path = config?.path();
-                if path.is_file() {
-                    config_files.push(format!(
-                        "/config/{}",
-                        path.file_name().unwrap().to_string_lossy()
-                    ));
-                }
-            }
+        let mut env = Vec::new();
+        let mut config_files = config_files
+            .iter()
+            .map(|path| format!("/config/{}", path.file_name().unwrap().to_string_lossy()))
+            .collect::<Vec<_>>();
 
-            env.push(format!("CONFIG_FILES={}", config_files.join(",")));
-        }
+        // Last, so nothing in the agent configs can override the signer
+        config_files.push(signer::signer_config_mount());
+        env.push(format!("CONFIG_FILES={}", config_files.join(",")));
 
-        let relay_chains_path = self.relay_chains_path();
-        if relay_chains_path.exists() {
-            let relay_chains = std::fs::read_to_string(relay_chains_path)?;
-            env.push(format!("HYP_RELAYCHAINS={relay_chains}"));
+        if !relay_chains.is_empty() {
+            env.push(format!("HYP_RELAYCHAINS={}", relay_chains.join(",")));
         }
 
-        container = container.env(env).binds(binds).cmd([
-            "./relayer",
-            "--db /hyperlane_db",
-            "--defaultSigner.key",
-            &format!("0x{secret}"),
-        ]);
+        let mut labels = self.owner()?.labels();
+        labels.insert(
+            ownership::OPERATOR_LABEL.to_string(),
+            tangle_ecdsa_pair.alloy_address()?.to_string(),
+        );
+        labels.insert(
+            ownership::CONFIG_VERSION_LABEL.to_string(),
+            version.to_string(),
+        );
+        labels.insert(
+            ownership::CONFIG_HASH_LABEL.to_string(),
+            self.config_hash()?.to_string(),
+        );
 
-        container.create().await?;
+        let config = Config {
+            image: Some(IMAGE.to_string()),
+            cmd: Some(vec![
+                String::from("./relayer"),
+                String::from("--db /hyperlane_db"),
+            ]),
+            env: Some(env),
+            labels: Some(labels),
+            host_config: Some(HostConfig {
+                binds: Some(binds),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let ContainerCreateResponse { id, warnings } = self
+            .connection
+            .create_container(None::<CreateContainerOptions<String>>, config)
+            .await?;
+        for warning in warnings {
+            sdk::warn!("{warning}");
+        }
 
         if self.env.test_mode {
-            let id = container.id().unwrap();
             self.connection
                 .connect_network(
                     "hyperlane_relayer_test_net",
                     ConnectNetworkOptions {
-                        container: id,
+                        container: id.as_str(),
                         ..Default::default()
                     },
                 )
                 .await?;
         }
 
-        container.start(false).await?;
-        *container_guard = container.id().map(ToString::to_string);
+        self.connection
+            .start_container(&id, None::<StartContainerOptions<String>>)
+            .await?;
+        *self.container.lock().await = Some(id.clone());
+        self.state.update(|state| state.set_container(id.clone()))?;
 
-        // Allow time to spin up
-        tokio::time::sleep(std::time::Duration::from_secs(20)).await;
-
-        let status = container.status().await?;
-
-        // Container is down, something's wrong.
-        if !status.unwrap().is_active() {
-            return Err(eyre!("Failed to start container, config error?"));
-        }
+        let signals = ReadySignal::from_settings(&self.settings.readiness, &relay_chains);
+        readiness::wait_until_ready(&self.connection, &id, &self.settings.readiness, signals)
+            .await?;
 
+        drop(signer_config);
         sdk::info!("Successfully started container");
 
         Ok(())
     }
 
+    /// The agent configs of config `version`, in order
+    fn agent_configs(&self, version: u64) -> Result<Vec<String>> {
+        Ok(self
+            .history
+            .config_files(version)?
+            .iter()
+            .map(std::fs::read_to_string)
+            .collect::<std::io::Result<Vec<_>>>()?)
+    }
+
+    /// The agent configs of config `version`, merged in order
+    fn agent_config(&self, version: u64) -> Result<AgentConfig> {
+        let (agent_config, _) = agent_config::merge_configs(&self.agent_configs(version)?);
+        Ok(agent_config)
+    }
+
+    /// The keys the relayer signs with on `relay_chains`
+    fn signers(&self, agent_config: &AgentConfig, relay_chains: &[String]) -> Result<Signers> {
+        let relay_chains = relay_chains
+            .iter()
+            .map(|name| RelayChain {
+                name: name.clone(),
+                // Chains we know nothing about were accepted by `set_config` before
+                // validation, and are assumed to be EVM chains
+                protocol: agent_config.protocol(name).unwrap_or(Protocol::Ethereum),
+                bech32_prefix: agent_config.bech32_prefix(name),
+            })
+            .collect::<Vec<_>>();
+
+        Signers::from_keystore(
+            &self.env.keystore(),
+            self.state.get().signer,
+            &relay_chains,
+            self.settings.per_chain_signers,
+        )
+    }
+
+    /// Fall back to the previous config version
+    ///
+    /// The fallback is consumed, so a failure of the previous version doesn't bounce back to the
+    /// one that was just reverted. With no previous version, the relayer is left stopped until
+    /// the next `set_config` or `rollback_config` job.
     async fn revert_configs(&self) -> Result<()> {
         sdk::error!("Container failed to start with new configs, reverting");
 
         self.remove_existing_container().await?;
 
-        let original_configs_path = self.original_agent_configs_path();
-        if !original_configs_path.exists() {
-            // There is no config to revert
-            return Err(eyre!("Configs failed to apply, with no fallback"));
-        }
-
-        let configs_path = self.agent_configs_path();
+        let Some(previous) = self.state.get().previous_version else {
+            sdk::warn!("No previous config version to fall back to, leaving the relayer stopped");
+            self.state.update(|state| state.active_version = None)?;
+            return Ok(());
+        };
 
-        sdk::debug!(
-            "Moving `{}` to `{}`",
-            original_configs_path.display(),
-            configs_path.display()
-        );
-        std::fs::remove_dir_all(&configs_path)?;
-        std::fs::rename(original_configs_path, configs_path)?;
-
-        let original_relay_chains = self.original_relay_chains_path();
-        if original_relay_chains.exists() {
-            let relay_chains_path = self.relay_chains_path();
-            sdk::debug!(
-                "Moving `{}` to `{}`",
-                original_relay_chains.display(),
-                relay_chains_path.display(),
-            );
-            std::fs::rename(original_relay_chains, relay_chains_path)?;
-        }
+        sdk::info!("Reverting to config version {previous}");
+        self.state.update(|state| {
+            state.active_version = Some(previous);
+            state.previous_version = None;
+        })?;
 
         self.spinup_container().await?;
         Ok(())
     }
 
-    pub async fn remove_existing_container(&self) -> Result<()> {
-        let mut container_id = self.container.lock().await;
-        if let Some(container_id) = container_id.take() {
-            sdk::warn!("Removing existing container...");
-            let mut c = Container::from_id(self.connection.client(), container_id).await?;
-            c.stop().await?;
-            c.remove(None).await?;
-        }
+    /// Start the relayer with config `version`, falling back to the current one on failure
+    ///
+    /// The apply is recorded in the [`Journal`], so if the blueprint dies part way through, or the
+    /// revert fails, it is rolled back on the next startup. The caller must hold the `lifecycle`
+    /// lock.
+    async fn apply_version(&self, version: u64) -> Result<()> {
+        let state = self.state.get();
+        let intent = ApplyIntent {
+            version,
+            active_version: state.active_version,
+            previous_version: state.previous_version,
+            stopped: state.stopped,
+        };
+        self.journal
+            .run(&intent, self.switch_to_version(version))
+            .await?;
+
+        let state = self.state.get();
+        let keep = [state.active_version, state.previous_version];
+        self.history
+            .prune(&keep.into_iter().flatten().collect::<Vec<_>>())?;
 
         Ok(())
     }
 
-    fn hyperlane_db_path(&self) -> PathBuf {
-        self.data_dir.join("hyperlane_db")
-    }
-
-    fn agent_configs_path(&self) -> PathBuf {
-        self.data_dir.join("agent_configs")
-    }
-
-    fn relay_chains_path(&self) -> PathBuf {
-        self.data_dir.join("relay_chains.txt")
-    }
+    async fn switch_to_version(&self, version: u64) -> Result<(), ApplyError> {
+        self.remove_existing_container()
+            .await
+            .map_err(ApplyError::Interrupted)?;
+        self.state
+            .update(|state| {
+                state.activate(version);
+                state.stopped = false;
+            })
+            .map_err(ApplyError::Interrupted)?;
+
+        if let Err(e) = self.spinup_container().await {
+            // Something went wrong spinning up the container, possibly bad config. Try to revert.
+            sdk::error!("{e}");
+            self.revert_configs()
+                .await
+                .map_err(ApplyError::Interrupted)?;
+            return Err(ApplyError::Reverted(e.wrap_err(format!(
+                "Relayer failed to start with config version {version}, reverted"
+            ))));
+        }
 
-    fn original_agent_configs_path(&self) -> PathBuf {
-        self.data_dir.join("agent_configs.orig")
+        Ok(())
     }
 
-    fn original_relay_chains_path(&self) -> PathBuf {
-        self.data_dir.join("relay_chains.txt.orig")
+    /// Roll back an apply that was interrupted by the blueprint dying
+    ///
+    /// The state from before the apply is restored, and [`Self::reconcile`] then replaces the
+    /// container if it was started with the interrupted version.
+    fn recover_interrupted_apply(&self) -> Result<()> {
+        self.history.clean_staging()?;
+        self.journal.roll_back(&self.state)?;
+        Ok(())
     }
-}
 
-pub const SET_CONFIG_JOB_ID: u8 = 0;
-
-pub async fn set_config(
-    Context(ctx): Context<Arc<HyperlaneContext>>,
-    TangleArgs2(Optional(config_urls), relay_chains): TangleArgs2<Optional<List<String>>, String>,
-) -> Result<TangleResult<u64>> {
-    let mut configs = Vec::new();
-    if let Some(List(config_urls)) = config_urls {
-        for config_url in config_urls {
-            // https://github.com/seanmonstar/reqwest/issues/178
-            let url = reqwest::Url::parse(&config_url)?;
-            if url.scheme() == "file" && ctx.env.test_mode {
-                let config = std::fs::read_to_string(url.to_file_path().unwrap())?;
-                configs.push(config);
-                continue;
+    pub async fn remove_existing_container(&self) -> Result<()> {
+        let mut container_id = self.container.lock().await;
+        if let Some(container_id) = container_id.take() {
+            sdk::warn!("Removing existing container...");
+            let mut c = match Container::from_id(self.connection.client(), &container_id).await {
+                Ok(c) => c,
+                Err(e) if is_not_found(&e) => {
+                    sdk::warn!("Container `{container_id}` no longer exists");
+                    self.state.update(state::RelayerState::clear_container)?;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if c.status().await?.is_some_and(|status| status.is_active()) {
+                c.stop().await?;
             }
-            configs.push(reqwest::get(config_url).await?.text().await?);
-        }
-    }
+            c.remove(Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            }))
+            .await?;
 
-    // TODO: First step, verify the config is valid. Is there an easy way to do so?
-    if relay_chains.is_empty() || !relay_chains.contains(',') {
-        return Err(eyre!(
-            "`relay_chains` is invalid, ensure it contains at least two chains"
-        ));
-    }
-
-    ctx.remove_existing_container().await?;
+            self.state.update(state::RelayerState::clear_container)?;
+        }
 
-    let configs_path = ctx.agent_configs_path();
-    if configs_path.exists() {
-        let orig_configs_path = ctx.original_agent_configs_path();
-        sdk::info!("Configs path exists, backing up.");
-        std::fs::rename(&configs_path, orig_configs_path)?;
-        std::fs::create_dir_all(&configs_path)?;
+        Ok(())
     }
 
-    let relay_chains_path = ctx.relay_chains_path();
-    if relay_chains_path.exists() {
-        let orig_relay_chains_path = ctx.original_relay_chains_path();
-        sdk::info!("Relay chains list exists, backing up.");
-        std::fs::rename(&relay_chains_path, orig_relay_chains_path)?;
+    fn secrets_dir(&self) -> PathBuf {
+        self.settings
+            .secrets_dir
+            .clone()
+            .unwrap_or_else(|| signer::default_secrets_dir(&self.data_dir))
     }
 
-    std::fs::create_dir_all(&configs_path)?;
-    if configs.is_empty() {
-        sdk::info!("No configs provided, using defaults");
-    } else {
-        // TODO: Limit number of configs?
-        for (index, config) in configs.iter().enumerate() {
-            std::fs::write(configs_path.join(format!("{index}.json")), config)?;
-        }
-        sdk::info!("New configs written to: {}", configs_path.display());
-    }
-
-    std::fs::write(&relay_chains_path, relay_chains)?;
-    sdk::info!("Relay chains written to: {}", relay_chains_path.display());
-
-    if let Err(e) = ctx.spinup_container().await {
-        // Something went wrong spinning up the container, possibly bad config. Try to revert.
-        sdk::error!("{e}");
-        ctx.revert_configs().await?;
+    fn hyperlane_db_path(&self) -> PathBuf {
+        self.data_dir.join("hyperlane_db")
     }
+}
 
-    Ok(TangleResult(0))
+fn is_not_found(e: &docktopus::container::Error) -> bool {
+    matches!(
+        e,
+        docktopus::container::Error::ContainerNotFound
+            | docktopus::container::Error::Bollard(
+                docktopus::bollard::errors::Error::DockerResponseServerError {
+                    status_code: 404,
+                    ..
+                }
+            )
+    )
 }
@@ -0,0 +1,56 @@
+//! Configs staged on the operator's disk
+//!
+//! Operators of private deployments can keep vetted configs in a local directory (see
+//! [`FetchSettings::local_config_dir`](crate::fetch::FetchSettings::local_config_dir)), and
+//! service owners refer to them as `local:<path>`, relative to the directory, or as `file://`
+//! URLs inside it. Nothing outside the directory can be read, whether through `..` or symlinks.
+
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+
+/// Resolve a `local:` or `file://` config URL to a file inside `dir`
+///
+/// # Errors
+///
+/// * The URL isn't a `local:` or `file://` URL
+/// * The URL points outside of `dir`, or to something other than a file
+pub fn resolve(dir: &Path, url: &Url) -> Result<PathBuf> {
+    let outside = || eyre!("Config `{url}` is not in the local config directory");
+
+    let dir = std::path::absolute(dir)?;
+    let path = match url.scheme() {
+        // Joining normalizes `.` and `..` segments, so the result can be checked as is
+        "local" => Url::from_directory_path(&dir)
+            .map_err(|()| eyre!("Invalid local config directory `{}`", dir.display()))?
+            .join(url.path())
+            .wrap_err_with(|| format!("Invalid local config URL `{url}`"))?,
+        "file" => url.clone(),
+        scheme => return Err(eyre!("`{scheme}` URLs are not local configs")),
+    };
+    let path = path
+        .to_file_path()
+        .map_err(|()| eyre!("Invalid local config URL `{url}`"))?;
+
+    // Checked before touching the filesystem, so nothing is revealed about files elsewhere
+    if !path.starts_with(&dir) {
+        return Err(outside());
+    }
+
+    // Symlinks may still lead out of the directory
+    let canonical_dir = dir
+        .canonicalize()
+        .wrap_err_with(|| format!("Local config directory `{}` is missing", dir.display()))?;
+    let canonical = path
+        .canonicalize()
+        .wrap_err_with(|| format!("Config `{url}` does not exist"))?;
+    if !canonical.starts_with(&canonical_dir) {
+        return Err(outside());
+    }
+    if !canonical.is_file() {
+        return Err(eyre!("Config `{url}` is not a file"));
+    }
+
+    Ok(canonical)
+}
@@ -0,0 +1,116 @@
+//! Labels marking the containers owned by this blueprint, and garbage collection of them
+
+use crate::HyperlaneContext;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use docktopus::bollard::container::{ListContainersOptions, RemoveContainerOptions};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const BLUEPRINT_ID_LABEL: &str = "tools.tangle.blueprint-id";
+pub const SERVICE_ID_LABEL: &str = "tools.tangle.service-id";
+pub const OPERATOR_LABEL: &str = "tools.tangle.operator";
+pub const CONFIG_VERSION_LABEL: &str = "xyz.hyperlane.relayer.config-version";
+pub const CONFIG_HASH_LABEL: &str = "xyz.hyperlane.relayer.config-hash";
+
+/// The labels identifying a container as belonging to this blueprint instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Owner {
+    pub blueprint_id: u64,
+    pub service_id: Option<u64>,
+}
+
+impl Owner {
+    pub fn labels(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (
+                BLUEPRINT_ID_LABEL.to_string(),
+                self.blueprint_id.to_string(),
+            ),
+            (SERVICE_ID_LABEL.to_string(), self.service_id_label()),
+        ])
+    }
+
+    /// Whether a container's labels mark it as ours
+    pub fn owns(&self, labels: &HashMap<String, String>) -> bool {
+        labels.get(BLUEPRINT_ID_LABEL) == Some(&self.blueprint_id.to_string())
+            && labels.get(SERVICE_ID_LABEL) == Some(&self.service_id_label())
+    }
+
+    fn service_id_label(&self) -> String {
+        self.service_id.map(|id| id.to_string()).unwrap_or_default()
+    }
+
+    fn filters(&self) -> HashMap<String, Vec<String>> {
+        let labels = self
+            .labels()
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        HashMap::from([(String::from("label"), labels)])
+    }
+}
+
+/// Remove every container carrying our labels, other than the one tracked in the context
+///
+/// The caller must hold the `lifecycle` lock, as a container being created is only tracked once
+/// started.
+///
+/// # Errors
+///
+/// * Unable to list the containers
+pub(crate) async fn collect_garbage(ctx: &HyperlaneContext) -> Result<()> {
+    let owner = ctx.owner()?;
+    let tracked = ctx.container_id().await;
+
+    let containers = ctx
+        .connection
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters: owner.filters(),
+            ..Default::default()
+        }))
+        .await?;
+
+    for container in containers {
+        let Some(id) = container.id else {
+            continue;
+        };
+
+        if tracked.as_deref() == Some(id.as_str()) {
+            continue;
+        }
+
+        sdk::warn!("Removing orphaned relayer container `{id}`");
+        if let Err(e) = ctx
+            .connection
+            .remove_container(
+                &id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+        {
+            sdk::error!("Failed to remove orphaned relayer container `{id}`: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Run [`collect_garbage`] every `interval`
+pub(crate) async fn collect_garbage_periodically(ctx: HyperlaneContext, interval: Duration) {
+    let mut interval = sdk::tokio::time::interval(interval);
+    // The first tick completes immediately, and the startup pass has already run
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        let _lifecycle = ctx.lifecycle.lock().await;
+        if let Err(e) = collect_garbage(&ctx).await {
+            sdk::error!("Container garbage collection failed: {e}");
+        }
+    }
+}
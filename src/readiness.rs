@@ -0,0 +1,262 @@
+//! Readiness checks for a freshly started relayer container
+
+use blueprint_sdk as sdk;
+use docktopus::bollard::Docker;
+use docktopus::bollard::container::{InspectContainerOptions, LogsOptions};
+use docktopus::bollard::models::ContainerInspectResponse;
+use futures::StreamExt;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The metric the relayer reports for each chain it is indexing
+const INDEXING_METRIC: &str = "hyperlane_contract_sync_block_height";
+
+/// Number of log lines attached to a failure
+const FAILURE_LOG_LINES: usize = 20;
+
+/// Settings for [`wait_until_ready`]
+#[derive(Debug, Clone)]
+pub struct ReadinessSettings {
+    /// How long to wait for the relayer to become ready before giving up
+    pub deadline: Duration,
+    /// The delay before the second poll, doubled on every subsequent poll
+    pub initial_interval: Duration,
+    /// The upper bound for the polling interval
+    pub max_interval: Duration,
+    /// The port the relayer serves its Prometheus metrics on
+    pub metrics_port: u16,
+    /// Require every relay chain to report indexing progress
+    pub require_chains_indexing: bool,
+    /// Patterns that must each appear in the relayer logs
+    pub log_patterns: Vec<String>,
+}
+
+impl Default for ReadinessSettings {
+    fn default() -> Self {
+        Self {
+            deadline: Duration::from_secs(60),
+            initial_interval: Duration::from_millis(500),
+            max_interval: Duration::from_secs(10),
+            metrics_port: 9090,
+            require_chains_indexing: true,
+            log_patterns: Vec::new(),
+        }
+    }
+}
+
+/// A condition that must hold for the relayer to be considered ready
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadySignal {
+    /// The metrics endpoint is serving
+    MetricsEndpoint,
+    /// The relayer reports indexing progress for the chain
+    ChainIndexing(String),
+    /// A log line contains the pattern
+    LogLine(String),
+}
+
+impl ReadySignal {
+    /// Build the signals for the given settings and relay chains
+    pub fn from_settings(settings: &ReadinessSettings, relay_chains: &[String]) -> Vec<Self> {
+        let mut signals = vec![ReadySignal::MetricsEndpoint];
+        if settings.require_chains_indexing {
+            signals.extend(relay_chains.iter().cloned().map(ReadySignal::ChainIndexing));
+        }
+
+        signals.extend(
+            settings
+                .log_patterns
+                .iter()
+                .cloned()
+                .map(ReadySignal::LogLine),
+        );
+        signals
+    }
+
+    fn needs_logs(&self) -> bool {
+        matches!(self, ReadySignal::LogLine(_))
+    }
+
+    /// Whether the signal holds, given the relayer's metrics (if they could be fetched) and logs
+    pub fn is_satisfied(&self, metrics: Option<&str>, logs: &str) -> bool {
+        match self {
+            ReadySignal::MetricsEndpoint => metrics.is_some(),
+            ReadySignal::ChainIndexing(chain) => {
+                let Some(metrics) = metrics else {
+                    return false;
+                };
+
+                let label = format!("chain=\"{chain}\"");
+                metrics
+                    .lines()
+                    .any(|line| line.starts_with(INDEXING_METRIC) && line.contains(&label))
+            }
+            ReadySignal::LogLine(pattern) => logs.contains(pattern.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for ReadySignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadySignal::MetricsEndpoint => f.write_str("metrics endpoint"),
+            ReadySignal::ChainIndexing(chain) => write!(f, "`{chain}` indexing"),
+            ReadySignal::LogLine(pattern) => write!(f, "log line `{pattern}`"),
+        }
+    }
+}
+
+/// The reason a relayer container failed its readiness check
+#[derive(Debug, thiserror::Error)]
+pub enum NotReady {
+    #[error("Relayer exited with code {exit_code} (OOM killed: {oom_killed}), last logs:\n{logs}")]
+    Exited {
+        exit_code: i64,
+        oom_killed: bool,
+        logs: String,
+    },
+    #[error("Relayer not ready after {elapsed:?}, still waiting on: {}", format_signals(.missing))]
+    DeadlineExceeded {
+        elapsed: Duration,
+        missing: Vec<ReadySignal>,
+    },
+    #[error("Failed to inspect relayer container: {0}")]
+    Docker(#[from] docktopus::bollard::errors::Error),
+}
+
+fn format_signals(signals: &[ReadySignal]) -> String {
+    signals
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Poll a started container until every signal is satisfied
+///
+/// The container state, logs and metrics endpoint are polled with an exponentially growing
+/// interval, until either all `signals` hold or the deadline in `settings` passes.
+///
+/// # Errors
+///
+/// See [`NotReady`]
+pub async fn wait_until_ready(
+    docker: &Docker,
+    container_id: &str,
+    settings: &ReadinessSettings,
+    signals: Vec<ReadySignal>,
+) -> Result<(), NotReady> {
+    let http = reqwest::Client::builder()
+        .timeout(settings.max_interval.max(Duration::from_secs(1)))
+        .build()
+        .expect("client settings are valid");
+
+    let start = Instant::now();
+    let mut interval = settings.initial_interval;
+    let mut pending = signals;
+
+    loop {
+        let inspect = docker
+            .inspect_container(container_id, None::<InspectContainerOptions>)
+            .await?;
+
+        let state = inspect.state.clone().unwrap_or_default();
+        if !state.running.unwrap_or(false) {
+            return Err(NotReady::Exited {
+                exit_code: state.exit_code.unwrap_or_default(),
+                oom_killed: state.oom_killed.unwrap_or(false),
+                logs: tail_logs(docker, container_id, FAILURE_LOG_LINES).await,
+            });
+        }
+
+        let logs = if pending.iter().any(ReadySignal::needs_logs) {
+            tail_logs(docker, container_id, usize::MAX).await
+        } else {
+            String::new()
+        };
+
+        let metrics = match container_ip(&inspect) {
+            Some(ip) => {
+                let url = format!("http://{ip}:{}/metrics", settings.metrics_port);
+                fetch_metrics(&http, &url).await
+            }
+            None => None,
+        };
+
+        pending.retain(|signal| !signal.is_satisfied(metrics.as_deref(), &logs));
+        if pending.is_empty() {
+            sdk::debug!("Relayer ready after {:?}", start.elapsed());
+            return Ok(());
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= settings.deadline {
+            return Err(NotReady::DeadlineExceeded {
+                elapsed,
+                missing: pending,
+            });
+        }
+
+        sdk::debug!(
+            "Relayer not ready yet, waiting on: {}",
+            format_signals(&pending)
+        );
+        sdk::tokio::time::sleep(interval.min(settings.deadline - elapsed)).await;
+        interval = (interval * 2).min(settings.max_interval);
+    }
+}
+
+async fn fetch_metrics(http: &reqwest::Client, url: &str) -> Option<String> {
+    let response = http.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    response.text().await.ok()
+}
+
+fn container_ip(inspect: &ContainerInspectResponse) -> Option<String> {
+    let settings = inspect.network_settings.as_ref()?;
+    settings
+        .networks
+        .iter()
+        .flat_map(|networks| networks.values())
+        .filter_map(|endpoint| endpoint.ip_address.clone())
+        .chain(settings.ip_address.clone())
+        .find(|ip| !ip.is_empty())
+}
+
+/// Fetch the last `lines` lines of the container's logs
+///
+/// Errors are logged and result in an empty string, logs are only used for diagnostics and
+/// [`ReadySignal::LogLine`].
+pub async fn tail_logs(docker: &Docker, container_id: &str, lines: usize) -> String {
+    let tail = if lines == usize::MAX {
+        String::from("all")
+    } else {
+        lines.to_string()
+    };
+
+    let mut stream = docker.logs(
+        container_id,
+        Some(LogsOptions {
+            stdout: true,
+            stderr: true,
+            tail,
+            ..Default::default()
+        }),
+    );
+
+    let mut logs = String::new();
+    while let Some(output) = stream.next().await {
+        match output {
+            Ok(output) => logs.push_str(&output.to_string()),
+            Err(e) => {
+                sdk::warn!("Failed to read relayer logs: {e}");
+                break;
+            }
+        }
+    }
+
+    logs
+}
@@ -0,0 +1,194 @@
+//! Agent configs built from a Hyperlane registry
+//!
+//! A registry, like <https://github.com/hyperlane-xyz/hyperlane-registry>, keeps every chain in
+//! `chains/<name>/`, with the chain's metadata in `metadata.yaml` and the addresses of its core
+//! contracts in `addresses.yaml`. The relayer's config for a chain is its metadata, with its
+//! addresses merged in.
+
+use crate::inline_config::{self, MAX_CONFIG_SIZE};
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use flate2::read::GzDecoder;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Component, Path};
+
+pub const METADATA_FILE: &str = "metadata.yaml";
+pub const ADDRESSES_FILE: &str = "addresses.yaml";
+
+/// The most bytes unpacked from a registry archive, to bound the work done on a hostile one
+pub const MAX_UNPACKED_SIZE: u64 = 256 * 1024 * 1024;
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// A chain's files in a registry
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryChain {
+    pub metadata: Option<Value>,
+    pub addresses: Option<Value>,
+}
+
+impl RegistryChain {
+    /// Store the parsed contents of `file`, either [`METADATA_FILE`] or [`ADDRESSES_FILE`]
+    pub fn insert(&mut self, file: &str, value: Value) {
+        if file == METADATA_FILE {
+            self.metadata = Some(value);
+        } else {
+            self.addresses = Some(value);
+        }
+    }
+}
+
+/// The path of `file` for `chain`, relative to the registry's root
+///
+/// # Errors
+///
+/// * `chain` isn't a valid registry chain name, which is lowercase alphanumeric
+pub fn chain_file(chain: &str, file: &str) -> Result<String> {
+    let valid = !chain.is_empty()
+        && chain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    if !valid {
+        return Err(eyre!("`{chain}` is not a valid registry chain name"));
+    }
+
+    Ok(format!("chains/{chain}/{file}"))
+}
+
+/// Read the files of `chains` from a registry archive
+///
+/// The archive is a tarball, optionally gzipped, of the registry's root, or of a single directory
+/// containing it (like GitHub's source archives).
+///
+/// # Errors
+///
+/// * The archive is corrupt, or unpacks to more than [`MAX_UNPACKED_SIZE`]
+/// * Any of the files are larger than [`MAX_CONFIG_SIZE`], or aren't YAML objects
+pub fn read_archive(archive: &[u8], chains: &[String]) -> Result<BTreeMap<String, RegistryChain>> {
+    let reader: Box<dyn Read + '_> = if inline_config::is_gzip(archive) {
+        Box::new(GzDecoder::new(archive))
+    } else {
+        Box::new(archive)
+    };
+    let mut archive = tar::Archive::new(reader.take(MAX_UNPACKED_SIZE));
+
+    let mut files = BTreeMap::<String, RegistryChain>::new();
+    for entry in archive
+        .entries()
+        .wrap_err("Registry archive is not a valid tarball")?
+    {
+        let entry = entry.wrap_err("Registry archive is corrupt")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?;
+        let Some((chain, file)) = chain_file_in(&path) else {
+            continue;
+        };
+        if !chains.iter().any(|c| c == chain) {
+            continue;
+        }
+
+        let (chain, file) = (chain.to_string(), file.to_string());
+        let contents = inline_config::read_limited(entry, MAX_CONFIG_SIZE)
+            .wrap_err("Registry archive is corrupt")?
+            .ok_or_else(|| {
+                eyre!("`chains/{chain}/{file}` is larger than {MAX_CONFIG_SIZE} bytes")
+            })?;
+        let value = parse_file(&chain, &file, &contents)?;
+
+        files.entry(chain).or_default().insert(&file, value);
+    }
+
+    Ok(files)
+}
+
+/// The chain and file name of `path`, if it's `[<root>/]chains/<chain>/<file>` for a chain file
+fn chain_file_in(path: &Path) -> Option<(&str, &str)> {
+    let components = path
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    match components.as_slice() {
+        ["chains", chain, file] | [_, "chains", chain, file]
+            if matches!(*file, METADATA_FILE | ADDRESSES_FILE) =>
+        {
+            Some((chain, file))
+        }
+        _ => None,
+    }
+}
+
+/// Parse a registry file, which must be a YAML object
+///
+/// # Errors
+///
+/// * The file isn't valid YAML, or isn't an object
+pub fn parse_file(chain: &str, file: &str, contents: &[u8]) -> Result<Value> {
+    let value = serde_yaml::from_slice::<Value>(contents)
+        .wrap_err_with(|| format!("`chains/{chain}/{file}` is not valid YAML"))?;
+    if !value.is_object() {
+        return Err(eyre!("`chains/{chain}/{file}` must be an object"));
+    }
+
+    Ok(value)
+}
+
+/// Build an agent config for `chains`, from their registry files
+///
+/// EVM chains without an `interchainGasPaymaster` get the zero address, since the relayer needs
+/// one, and registries of chains without gas payments may leave it out.
+///
+/// # Errors
+///
+/// * Any of `chains` are missing from the registry, or are missing a file
+/// * A chain's metadata names a different chain
+pub fn build_agent_config(
+    chains: &[String],
+    registry: &BTreeMap<String, RegistryChain>,
+) -> Result<String> {
+    let mut configs = Map::new();
+    for chain in chains {
+        let Some(RegistryChain {
+            metadata: Some(metadata),
+            addresses: Some(addresses),
+        }) = registry.get(chain)
+        else {
+            return Err(eyre!(
+                "`{chain}` needs both `{}` and `{}` in the registry",
+                chain_file(chain, METADATA_FILE)?,
+                chain_file(chain, ADDRESSES_FILE)?
+            ));
+        };
+
+        let mut config = metadata.as_object().cloned().unwrap_or_default();
+        if let Some(name) = config.get("name")
+            && name.as_str() != Some(chain)
+        {
+            return Err(eyre!(
+                "The registry metadata of `{chain}` is for chain {name}"
+            ));
+        }
+        config.insert(String::from("name"), Value::from(chain.as_str()));
+
+        if let Some(addresses) = addresses.as_object() {
+            config.extend(addresses.clone());
+        }
+
+        if config.get("protocol").and_then(Value::as_str) == Some("ethereum") {
+            config
+                .entry("interchainGasPaymaster")
+                .or_insert_with(|| Value::from(ZERO_ADDRESS));
+        }
+
+        configs.insert(chain.clone(), Value::Object(config));
+    }
+
+    Ok(serde_json::to_string_pretty(&json!({ "chains": configs }))?)
+}
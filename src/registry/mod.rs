@@ -0,0 +1,147 @@
+//! A bundled snapshot of the chains in Hyperlane's default agent config
+//!
+//! The relayer image ships with full configs for these chains, so they can be relayed without
+//! supplying any configs, and supplied configs only need to override the fields they change. The
+//! blueprint can't read the image's configs, so the snapshot holds a copy of each chain's config:
+//! it's what relay chains are checked against, and what the
+//! [effective config](crate::agent_config::render) starts from.
+//!
+//! The snapshot is tied to a release of the image, see [`SNAPSHOT_VERSION`], and is generated
+//! from the agent configs in that release with [`build_snapshot`]. To regenerate it after updating
+//! the image, run the ignored `default_chains_match_image_configs` test with
+//! `UPDATE_DEFAULT_CHAINS=1` (see `tests/registry.rs`).
+
+pub mod layout;
+
+use crate::agent_config::Protocol;
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// The release of the relayer image the snapshot was taken from
+pub const SNAPSHOT_VERSION: &str = "agents-v1.2.0";
+
+/// Where the relayer image keeps its default agent configs
+pub const IMAGE_CONFIG_DIR: &str = "/app/config";
+
+/// The default agent configs in [`IMAGE_CONFIG_DIR`], the same as `rust/main/config` in the
+/// Hyperlane monorepo
+pub const IMAGE_CONFIG_FILES: [&str; 2] = ["mainnet_config.json", "testnet_config.json"];
+
+const DEFAULT_CHAINS: &str = include_str!("default_chains.json");
+
+#[derive(Deserialize)]
+struct Snapshot {
+    version: String,
+    chains: BTreeMap<String, Map<String, Value>>,
+}
+
+static CHAINS: LazyLock<BTreeMap<String, DefaultChain>> = LazyLock::new(|| {
+    let snapshot = serde_json::from_str::<Snapshot>(DEFAULT_CHAINS)
+        .expect("the bundled default chains should be valid");
+    assert_eq!(
+        snapshot.version, SNAPSHOT_VERSION,
+        "the bundled default chains are from another release"
+    );
+    snapshot
+        .chains
+        .into_iter()
+        .map(|(name, config)| {
+            let chain = DefaultChain::from_config(config)
+                .unwrap_or_else(|e| panic!("the bundled default chain `{name}` is invalid: {e}"));
+            (name, chain)
+        })
+        .collect()
+});
+
+/// A chain in the relayer image's default config
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultChain {
+    pub domain_id: u32,
+    pub protocol: Protocol,
+    /// The address prefix of Cosmos chains
+    #[serde(default)]
+    pub bech32_prefix: Option<String>,
+    /// The chain's whole config in the image, as it appears in the agent config's `chains`
+    #[serde(skip)]
+    pub config: Map<String, Value>,
+}
+
+impl DefaultChain {
+    /// Read a chain from its `config` in the image
+    ///
+    /// # Errors
+    ///
+    /// * The config is missing its `domainId` or `protocol`, or they're invalid
+    pub fn from_config(config: Map<String, Value>) -> serde_json::Result<Self> {
+        let mut chain = serde_json::from_value::<Self>(Value::Object(config.clone()))?;
+        chain.config = config;
+        Ok(chain)
+    }
+}
+
+/// Look up a chain in the default config
+pub fn default_chain(name: &str) -> Option<&'static DefaultChain> {
+    CHAINS.get(name)
+}
+
+/// Every chain in the default config, by name
+pub fn default_chains() -> &'static BTreeMap<String, DefaultChain> {
+    &CHAINS
+}
+
+/// The image's config of `chains`, as an agent config, for the chains in the default config
+///
+/// Chains that aren't in the default config are left out.
+pub fn default_config(chains: &[String]) -> Value {
+    let chains = chains
+        .iter()
+        .filter_map(|name| {
+            let mut config = default_chain(name)?.config.clone();
+            config
+                .entry("name")
+                .or_insert_with(|| Value::from(name.as_str()));
+            Some((name.clone(), Value::Object(config)))
+        })
+        .collect::<Map<_, _>>();
+
+    serde_json::json!({ "chains": chains })
+}
+
+/// Build the bundled snapshot from the default agent configs of image release `version`
+///
+/// Every chain in the configs is kept as is.
+///
+/// # Errors
+///
+/// * Any of the configs aren't JSON objects with `chains`
+/// * A chain is missing its `domainId` or `protocol`
+/// * A chain is in more than one config
+pub fn build_snapshot(version: &str, agent_configs: &[String]) -> Result<String> {
+    let mut chains = BTreeMap::new();
+    for (index, config) in agent_configs.iter().enumerate() {
+        let config = serde_json::from_str::<Value>(config)
+            .wrap_err_with(|| format!("Default config {index} is not valid JSON"))?;
+        let Some(Value::Object(config_chains)) = config.get("chains") else {
+            return Err(eyre!("Default config {index} has no `chains` object"));
+        };
+
+        for (name, chain) in config_chains {
+            let Value::Object(chain) = chain else {
+                return Err(eyre!("Default chain `{name}` is not an object"));
+            };
+            DefaultChain::from_config(chain.clone())
+                .wrap_err_with(|| format!("Default chain `{name}` is invalid"))?;
+            if chains.insert(name.clone(), chain.clone()).is_some() {
+                return Err(eyre!("Default chain `{name}` is in more than one config"));
+            }
+        }
+    }
+
+    let snapshot = serde_json::json!({ "version": version, "chains": chains });
+    Ok(serde_json::to_string_pretty(&snapshot)? + "\n")
+}
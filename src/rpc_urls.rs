@@ -0,0 +1,100 @@
+//! RPC overrides set with the `set_rpc_urls` job
+//!
+//! The overrides are stored as an overlay config next to a version's agent configs (see
+//! [`RPC_URLS_FILE`](crate::history::RPC_URLS_FILE)), and applied after them, so the RPCs of any
+//! chain can be swapped without resubmitting its config.
+
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// How the relayer uses a chain's RPCs, see `defaultRpcConsensusType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RpcConsensusType {
+    /// Use the first RPC, moving on to the next when one fails
+    Fallback,
+    /// Query every RPC, and only trust responses a majority agree on
+    Quorum,
+}
+
+impl FromStr for RpcConsensusType {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "fallback" => Ok(Self::Fallback),
+            "quorum" => Ok(Self::Quorum),
+            _ => Err(eyre!(
+                "Unknown RPC consensus type `{s}`, expected `fallback` or `quorum`"
+            )),
+        }
+    }
+}
+
+impl Display for RpcConsensusType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fallback => f.write_str("fallback"),
+            Self::Quorum => f.write_str("quorum"),
+        }
+    }
+}
+
+/// Parse a list of `<chain>=<url>` entries into each chain's RPC URLs
+///
+/// A chain can be given multiple times, its URLs are kept in order.
+///
+/// # Errors
+///
+/// * Any of the entries are missing a chain or URL
+pub fn parse_rpc_urls(entries: &[String]) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut rpc_urls = BTreeMap::<String, Vec<String>>::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let Some((chain, url)) = entry.split_once('=') else {
+            return Err(eyre!(
+                "RPC URL {index} is invalid, expected `<chain>=<url>`, got `{entry}`"
+            ));
+        };
+
+        let (chain, url) = (chain.trim(), url.trim());
+        if chain.is_empty() || url.is_empty() {
+            return Err(eyre!(
+                "RPC URL {index} is invalid, expected `<chain>=<url>`, got `{entry}`"
+            ));
+        }
+
+        rpc_urls
+            .entry(chain.to_string())
+            .or_default()
+            .push(url.to_string());
+    }
+
+    Ok(rpc_urls)
+}
+
+/// Build the overlay config for a set of RPC overrides
+///
+/// The overlay only sets the `rpcUrls` of the given chains, and `defaultRpcConsensusType` if
+/// `consensus_type` is given.
+pub fn overlay(
+    rpc_urls: &BTreeMap<String, Vec<String>>,
+    consensus_type: Option<RpcConsensusType>,
+) -> Result<String> {
+    let chains = rpc_urls
+        .iter()
+        .map(|(chain, urls)| {
+            let urls = urls.iter().map(|url| json!({ "http": url })).collect();
+            (chain.clone(), json!({ "rpcUrls": Value::Array(urls) }))
+        })
+        .collect::<Map<_, _>>();
+
+    let mut overlay = json!({ "chains": chains });
+    if let Some(consensus_type) = consensus_type {
+        overlay["defaultRpcConsensusType"] = Value::from(consensus_type.to_string());
+    }
+
+    Ok(serde_json::to_string_pretty(&overlay)?)
+}
@@ -0,0 +1,214 @@
+use crate::balances::BalanceSettings;
+use crate::fetch::FetchSettings;
+use crate::readiness::ReadinessSettings;
+use crate::supervisor::SupervisorSettings;
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+use std::fmt::Display;
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Operator-side settings for the relayer
+///
+/// These are not controlled by the service owner, and are loaded from `HYPERLANE_*` environment
+/// variables with [`Settings::from_env`].
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub readiness: ReadinessSettings,
+    pub supervisor: SupervisorSettings,
+    pub balances: BalanceSettings,
+    pub fetch: FetchSettings,
+    /// How often to remove orphaned relayer containers
+    pub gc_interval: Duration,
+    /// How many config versions to keep for `rollback_config`
+    pub config_history: usize,
+    /// Where to write the relayer's keys, see [`default_secrets_dir`](crate::signer::default_secrets_dir)
+    pub secrets_dir: Option<PathBuf>,
+    /// Whether to give every relay chain its own signer, see [`HexKey::derive`](crate::signer::HexKey::derive)
+    pub per_chain_signers: bool,
+    /// The largest config payload accepted by `set_config_inline`, before decompression
+    pub max_inline_config: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            readiness: ReadinessSettings::default(),
+            supervisor: SupervisorSettings::default(),
+            balances: BalanceSettings::default(),
+            fetch: FetchSettings::default(),
+            gc_interval: Duration::from_secs(300),
+            config_history: 10,
+            secrets_dir: None,
+            per_chain_signers: false,
+            max_inline_config: 32 * 1024,
+        }
+    }
+}
+
+impl Settings {
+    /// Load the settings from the environment, falling back to the defaults for any unset variable
+    ///
+    /// # Errors
+    ///
+    /// * Any of the variables are set, but fail to parse
+    pub fn from_env() -> Result<Self> {
+        let mut settings = Settings::default();
+
+        let readiness = &mut settings.readiness;
+        if let Some(secs) = var("HYPERLANE_READINESS_DEADLINE_SECS")? {
+            readiness.deadline = Duration::from_secs(secs);
+        }
+        if let Some(interval) = interval_var(
+            "HYPERLANE_READINESS_INITIAL_INTERVAL_MS",
+            Duration::from_millis,
+        )? {
+            readiness.initial_interval = interval;
+        }
+        if let Some(interval) =
+            interval_var("HYPERLANE_READINESS_MAX_INTERVAL_MS", Duration::from_millis)?
+        {
+            readiness.max_interval = interval;
+        }
+        if let Some(port) = var("HYPERLANE_METRICS_PORT")? {
+            readiness.metrics_port = port;
+        }
+        if let Some(require) = var("HYPERLANE_READINESS_REQUIRE_INDEXING")? {
+            readiness.require_chains_indexing = require;
+        }
+        if let Some(patterns) = list_var("HYPERLANE_READINESS_LOG_PATTERNS") {
+            readiness.log_patterns = patterns;
+        }
+
+        let supervisor = &mut settings.supervisor;
+        if let Some(max_restarts) = var("HYPERLANE_SUPERVISOR_MAX_RESTARTS")? {
+            supervisor.max_restarts = max_restarts;
+        }
+        if let Some(ms) = var("HYPERLANE_SUPERVISOR_BACKOFF_INITIAL_MS")? {
+            supervisor.backoff_initial = Duration::from_millis(ms);
+        }
+        if let Some(secs) = var("HYPERLANE_SUPERVISOR_BACKOFF_MAX_SECS")? {
+            supervisor.backoff_max = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var("HYPERLANE_SUPERVISOR_STABLE_SECS")? {
+            supervisor.stable_period = Duration::from_secs(secs);
+        }
+
+        let balances = &mut settings.balances;
+        if let Some(interval) =
+            interval_var("HYPERLANE_BALANCE_INTERVAL_SECS", Duration::from_secs)?
+        {
+            balances.interval = interval;
+        }
+        if let Some(min_balance) = var("HYPERLANE_MIN_SIGNER_BALANCE")? {
+            balances.min_balance = min_balance;
+        }
+        if let Some(entries) = list_var("HYPERLANE_MIN_SIGNER_BALANCES") {
+            for entry in entries {
+                let (chain, min_balance) = entry.split_once('=').ok_or_else(|| {
+                    eyre!("Invalid value for `HYPERLANE_MIN_SIGNER_BALANCES`: expected `chain=balance`, got `{entry}`")
+                })?;
+                let min_balance = min_balance
+                    .trim()
+                    .parse()
+                    .map_err(|e| eyre!("Invalid value for `HYPERLANE_MIN_SIGNER_BALANCES`: {e}"))?;
+                balances
+                    .min_balances
+                    .insert(chain.trim().to_string(), min_balance);
+            }
+        }
+        if let Some(secs) = var("HYPERLANE_BALANCE_RPC_TIMEOUT_SECS")? {
+            balances.rpc_timeout = Duration::from_secs(secs);
+        }
+
+        let fetch = &mut settings.fetch;
+        if let Some(secs) = var("HYPERLANE_FETCH_REQUEST_TIMEOUT_SECS")? {
+            fetch.request_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var("HYPERLANE_FETCH_TOTAL_TIMEOUT_SECS")? {
+            fetch.total_timeout = Duration::from_secs(secs);
+        }
+        if let Some(bytes) = var("HYPERLANE_FETCH_MAX_BYTES")? {
+            fetch.max_body_size = bytes;
+        }
+        if let Some(max_urls) = var("HYPERLANE_FETCH_MAX_URLS")? {
+            fetch.max_urls = max_urls;
+        }
+        if let Some(max_redirects) = var("HYPERLANE_FETCH_MAX_REDIRECTS")? {
+            fetch.max_redirects = max_redirects;
+        }
+        if let Some(max_parallel) = var("HYPERLANE_FETCH_MAX_PARALLEL")? {
+            fetch.max_parallel = max_parallel;
+        }
+        if let Some(retries) = var("HYPERLANE_FETCH_RETRIES")? {
+            fetch.retries = retries;
+        }
+        if let Some(ms) = var("HYPERLANE_FETCH_RETRY_BACKOFF_MS")? {
+            fetch.retry_backoff = Duration::from_millis(ms);
+        }
+        if let Some(allow) = var("HYPERLANE_FETCH_ALLOW_HTTP")? {
+            fetch.allow_http = allow;
+        }
+        if let Some(allow) = var("HYPERLANE_FETCH_ALLOW_PRIVATE_IPS")? {
+            fetch.allow_private_ips = allow;
+        }
+        if let Some(gateway) = var("HYPERLANE_IPFS_GATEWAY")? {
+            fetch.ipfs_gateway = Some(gateway);
+        }
+        if let Some(dir) = var("HYPERLANE_LOCAL_CONFIG_DIR")? {
+            fetch.local_config_dir = Some(dir);
+        }
+
+        if let Some(interval) = interval_var("HYPERLANE_GC_INTERVAL_SECS", Duration::from_secs)? {
+            settings.gc_interval = interval;
+        }
+        if let Some(config_history) = var("HYPERLANE_CONFIG_HISTORY")? {
+            settings.config_history = config_history;
+        }
+        if let Some(dir) = var("HYPERLANE_SECRETS_DIR")? {
+            settings.secrets_dir = Some(dir);
+        }
+        if let Some(per_chain) = var("HYPERLANE_PER_CHAIN_SIGNERS")? {
+            settings.per_chain_signers = per_chain;
+        }
+        if let Some(bytes) = var("HYPERLANE_MAX_INLINE_CONFIG_BYTES")? {
+            settings.max_inline_config = bytes;
+        }
+
+        Ok(settings)
+    }
+}
+
+fn var<T>(name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| eyre!("Invalid value for `{name}`: {e}")),
+        Err(_) => Ok(None),
+    }
+}
+
+/// A period for a timer, in the units of `unit`, which can't be zero
+fn interval_var(name: &str, unit: fn(u64) -> Duration) -> Result<Option<Duration>> {
+    Ok(var::<NonZeroU64>(name)?.map(|period| unit(period.get())))
+}
+
+fn list_var(name: &str) -> Option<Vec<String>> {
+    let value = std::env::var(name).ok()?;
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
@@ -0,0 +1,417 @@
+//! The relayer's signer keys, and delivering them to the container
+//!
+//! Keys are never passed on the command line or in the environment, where they would show up in
+//! `docker inspect` and the host's process list. They are written to a config file in a private
+//! directory (on tmpfs where available), mounted read-only into the container, and removed once
+//! the relayer has started.
+
+use crate::agent_config::Protocol;
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::{WrapErr, eyre};
+use sdk::alloy::hex;
+use sdk::alloy::primitives::{Address, Keccak256, keccak256};
+use sdk::alloy::signers::local::PrivateKeySigner;
+use sdk::crypto::sp_core::{SpEcdsa, SpEcdsaPublic, SpEd25519};
+use sdk::crypto::tangle_pair_signer::TanglePairSigner;
+use sdk::keystore::Keystore;
+use sdk::keystore::backends::Backend;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Where the secrets directory is mounted in the container
+pub const SECRETS_MOUNT: &str = "/secrets";
+const SIGNER_CONFIG_FILE: &str = "signer.json";
+
+/// The default secrets directory for a blueprint using `data_dir`
+///
+/// This is on `/dev/shm` if available, so keys never touch the disk.
+pub fn default_secrets_dir(data_dir: &Path) -> PathBuf {
+    let shm = Path::new("/dev/shm");
+    if shm.is_dir() {
+        let id = keccak256(data_dir.as_os_str().as_encoded_bytes());
+        return shm.join(format!("hyperlane-relayer-{}", hex::encode(&id[..8])));
+    }
+
+    data_dir.join("secrets")
+}
+
+/// Domain separation for per-chain key derivation
+const DERIVATION_CONTEXT: &[u8] = b"hyperlane-relayer";
+
+/// A relay chain, with what's needed to pick its signer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayChain {
+    pub name: String,
+    pub protocol: Protocol,
+    /// The account prefix, for Cosmos chains
+    pub bech32_prefix: Option<String>,
+}
+
+/// The signer for a single chain
+#[derive(Debug, Clone)]
+pub enum ChainSigner {
+    /// A secp256k1 key, for EVM chains
+    Ethereum(HexKey),
+    /// An ed25519 secret key, for Sealevel chains
+    Sealevel(HexKey),
+    /// A secp256k1 key and bech32 account prefix, for Cosmos chains
+    Cosmos { key: HexKey, prefix: String },
+}
+
+impl ChainSigner {
+    pub fn key(&self) -> &HexKey {
+        match self {
+            ChainSigner::Ethereum(key) | ChainSigner::Sealevel(key) => key,
+            ChainSigner::Cosmos { key, .. } => key,
+        }
+    }
+}
+
+/// The keys the relayer signs with
+#[derive(Debug)]
+pub struct Signers {
+    /// The secp256k1 key used for every EVM chain without its own key
+    pub default: HexKey,
+    /// Signers for individual chains, by chain name
+    pub chains: BTreeMap<String, ChainSigner>,
+}
+
+impl Signers {
+    /// Load the signers for `relay_chains` from the keystore
+    ///
+    /// EVM chains use the keystore's ECDSA key with address `signer` (or the first one, see
+    /// [`HexKey::from_keystore`]) as the default signer, unless `per_chain` is set.
+    /// Cosmos chains use the same key with their account prefix, and Sealevel chains use the
+    /// keystore's ed25519 key. With `per_chain`, every chain gets a key derived from these with
+    /// [`HexKey::derive`].
+    ///
+    /// # Errors
+    ///
+    /// * There is no ECDSA key in the keystore, or none with address `signer`
+    /// * There is a Sealevel relay chain, and no ed25519 key in the keystore
+    /// * A Cosmos relay chain has no `bech32Prefix`
+    /// * A relay chain uses a protocol we can't sign for
+    pub fn from_keystore(
+        keystore: &Keystore,
+        signer: Option<Address>,
+        relay_chains: &[RelayChain],
+        per_chain: bool,
+    ) -> Result<Self> {
+        let default = HexKey::from_keystore(keystore, signer)?;
+        let mut ed25519 = None;
+
+        let mut chains = BTreeMap::new();
+        for RelayChain {
+            name,
+            protocol,
+            bech32_prefix,
+        } in relay_chains
+        {
+            let signer = match protocol {
+                Protocol::Ethereum if per_chain => ChainSigner::Ethereum(default.derive(name)?),
+                Protocol::Ethereum => continue,
+                Protocol::Cosmos | Protocol::CosmosNative => {
+                    let Some(prefix) = bech32_prefix.clone() else {
+                        return Err(eyre!(
+                            "Cosmos relay chain `{name}` has no `bech32Prefix` in its config"
+                        ));
+                    };
+                    let key = if per_chain {
+                        default.derive(name)?
+                    } else {
+                        default.clone()
+                    };
+                    ChainSigner::Cosmos { key, prefix }
+                }
+                Protocol::Sealevel => {
+                    let seed = match &ed25519 {
+                        Some(seed) => seed,
+                        None => ed25519.insert(HexKey::ed25519_from_keystore(keystore)?),
+                    };
+                    let key = if per_chain {
+                        seed.derive_ed25519(name)?
+                    } else {
+                        seed.clone()
+                    };
+                    ChainSigner::Sealevel(key)
+                }
+                Protocol::Fuel | Protocol::Starknet => {
+                    return Err(eyre!(
+                        "Signing for {protocol} relay chains is not supported (`{name}`)"
+                    ));
+                }
+            };
+
+            chains.insert(name.clone(), signer);
+        }
+
+        Ok(Self { default, chains })
+    }
+
+    /// The signer for `chain`, `None` if it uses the default signer
+    pub fn for_chain(&self, chain: &str) -> Option<&ChainSigner> {
+        self.chains.get(chain)
+    }
+
+    /// The key used on the EVM chain `chain`
+    pub fn for_evm_chain(&self, chain: &str) -> &HexKey {
+        match self.for_chain(chain) {
+            Some(ChainSigner::Ethereum(key)) => key,
+            _ => &self.default,
+        }
+    }
+}
+
+/// A `0x` prefixed, hex encoded private key, zeroed when dropped
+#[derive(Clone)]
+pub struct HexKey(Zeroizing<String>);
+
+impl HexKey {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        // Reserved up front and encoded in place, so the key is never in a buffer that isn't zeroed
+        let mut key = Zeroizing::new(String::with_capacity(2 + 2 * bytes.len()));
+        key.push_str("0x");
+        for byte in bytes {
+            for nibble in [byte >> 4, byte & 0xf] {
+                key.push(char::from_digit(u32::from(nibble), 16).expect("nibbles are hex digits"));
+            }
+        }
+        Self(key)
+    }
+
+    fn to_bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        Ok(Zeroizing::new(hex::decode(self.expose())?))
+    }
+
+    /// The seed of the first ed25519 key in the keystore
+    ///
+    /// # Errors
+    ///
+    /// * There is no ed25519 key in the keystore
+    pub fn ed25519_from_keystore(keystore: &Keystore) -> Result<Self> {
+        let ed25519_pub = keystore
+            .first_local::<SpEd25519>()
+            .wrap_err("Sealevel relay chains need an ed25519 key in the keystore")?;
+        let ed25519_pair = keystore.get_secret::<SpEd25519>(&ed25519_pub)?;
+        let seed = Zeroizing::new(ed25519_pair.0.seed());
+        Ok(Self::from_bytes(seed.as_slice()))
+    }
+
+    fn derive_bytes(&self, chain: &str) -> Result<Zeroizing<[u8; 32]>> {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.to_bytes()?.as_slice());
+        hasher.update(DERIVATION_CONTEXT);
+        hasher.update(chain.as_bytes());
+        Ok(Zeroizing::new(hasher.finalize().0))
+    }
+
+    /// Deterministically derive a secp256k1 key for `chain`
+    ///
+    /// The key is `keccak256(self || "hyperlane-relayer" || chain)`, rehashed in the (practically
+    /// impossible) case that it isn't a valid secp256k1 scalar.
+    ///
+    /// # Errors
+    ///
+    /// * This key is malformed
+    pub fn derive(&self, chain: &str) -> Result<Self> {
+        let mut candidate = self.derive_bytes(chain)?;
+        while PrivateKeySigner::from_slice(candidate.as_slice()).is_err() {
+            candidate = Zeroizing::new(keccak256(candidate.as_slice()).0);
+        }
+
+        Ok(Self::from_bytes(candidate.as_slice()))
+    }
+
+    /// Deterministically derive an ed25519 key for `chain`, the same way as [`Self::derive`]
+    ///
+    /// # Errors
+    ///
+    /// * This key is malformed
+    pub fn derive_ed25519(&self, chain: &str) -> Result<Self> {
+        // Any 32 bytes make a valid ed25519 secret key
+        Ok(Self::from_bytes(self.derive_bytes(chain)?.as_slice()))
+    }
+
+    /// The EVM address of this key
+    ///
+    /// # Errors
+    ///
+    /// * This key is malformed
+    pub fn address(&self) -> Result<Address> {
+        Ok(self.wallet()?.address())
+    }
+
+    /// A local signer for sending transactions with this key
+    ///
+    /// # Errors
+    ///
+    /// * This key is malformed
+    pub fn wallet(&self) -> Result<PrivateKeySigner> {
+        Ok(PrivateKeySigner::from_slice(self.to_bytes()?.as_slice())?)
+    }
+
+    /// The ECDSA key in the keystore with the EVM address `address`, or the first one if `None`
+    ///
+    /// # Errors
+    ///
+    /// * There is no ECDSA key in the keystore
+    /// * There is no ECDSA key with address `address` in the keystore
+    pub fn from_keystore(keystore: &Keystore, address: Option<Address>) -> Result<Self> {
+        let Some(address) = address else {
+            return Self::ecdsa(keystore, &keystore.first_local::<SpEcdsa>()?);
+        };
+
+        for ecdsa_pub in keystore.list_local::<SpEcdsa>()? {
+            let key = Self::ecdsa(keystore, &ecdsa_pub)?;
+            if key.address()? == address {
+                return Ok(key);
+            }
+        }
+
+        Err(eyre!(
+            "There is no ECDSA key with address {address} in the keystore"
+        ))
+    }
+
+    /// Generate a new ECDSA key, and add it to the keystore
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the key to the keystore
+    pub fn generate(keystore: &Keystore) -> Result<Self> {
+        let ecdsa_pub = keystore.generate::<SpEcdsa>(None)?;
+        Self::ecdsa(keystore, &ecdsa_pub)
+    }
+
+    fn ecdsa(keystore: &Keystore, ecdsa_pub: &SpEcdsaPublic) -> Result<Self> {
+        let ecdsa_pair = keystore.get_secret::<SpEcdsa>(ecdsa_pub)?;
+        let signer = TanglePairSigner::new(ecdsa_pair.0);
+
+        let key = signer.alloy_key()?;
+        let bytes = Zeroizing::new(key.to_bytes().0);
+        Ok(Self::from_bytes(bytes.as_slice()))
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Debug for HexKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HexKey(<redacted>)")
+    }
+}
+
+/// A file holding secrets, removed when dropped
+#[derive(Debug)]
+pub struct SecretFile {
+    path: PathBuf,
+}
+
+impl SecretFile {
+    /// Write `contents` to `dir/name`, readable only by the current user
+    ///
+    /// # Errors
+    ///
+    /// * Unable to create `dir`, or write the file
+    pub fn write(dir: &Path, name: &str, contents: &[u8]) -> Result<Self> {
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        // In case the directory already existed with looser permissions
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+
+        let path = dir.join(name);
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        let file_guard = Self { path };
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        Ok(file_guard)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SecretFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            sdk::warn!(
+                "Failed to remove secret file `{}`: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Write the relayer config setting the `defaultSigner`, and a `chains.<name>.signer` for each
+/// chain with its own signer
+///
+/// The JSON is built by hand, so the keys are never copied into buffers that aren't zeroed.
+///
+/// # Errors
+///
+/// * Unable to write the file
+pub fn write_signer_config(dir: &Path, signers: &Signers) -> Result<SecretFile> {
+    // Reserved up front, since growing the string would leave copies of the keys behind
+    let capacity = 128
+        + signers
+            .chains
+            .iter()
+            .map(|(chain, signer)| match signer {
+                ChainSigner::Cosmos { prefix, .. } => chain.len() + prefix.len() + 160,
+                _ => chain.len() + 160,
+            })
+            .sum::<usize>();
+    let mut config = Zeroizing::new(String::with_capacity(capacity));
+    config.push_str("{\"defaultSigner\":");
+    push_hex_key(&mut config, &signers.default);
+
+    config.push_str(",\"chains\":{");
+    for (index, (chain, signer)) in signers.chains.iter().enumerate() {
+        if index > 0 {
+            config.push(',');
+        }
+        config.push_str(&serde_json::to_string(chain)?);
+        config.push_str(":{\"signer\":");
+        match signer {
+            ChainSigner::Ethereum(key) | ChainSigner::Sealevel(key) => {
+                push_hex_key(&mut config, key);
+            }
+            ChainSigner::Cosmos { key, prefix } => {
+                config.push_str("{\"type\":\"cosmosKey\",\"key\":\"");
+                config.push_str(key.expose());
+                config.push_str("\",\"prefix\":");
+                config.push_str(&serde_json::to_string(prefix)?);
+                config.push('}');
+            }
+        }
+        config.push('}');
+    }
+    config.push_str("}}");
+
+    SecretFile::write(dir, SIGNER_CONFIG_FILE, config.as_bytes())
+}
+
+fn push_hex_key(config: &mut String, key: &HexKey) {
+    config.push_str("{\"type\":\"hexKey\",\"key\":\"");
+    config.push_str(key.expose());
+    config.push_str("\"}");
+}
+
+/// The path of the signer config in the container
+pub fn signer_config_mount() -> String {
+    format!("{SECRETS_MOUNT}/{SIGNER_CONFIG_FILE}")
+}
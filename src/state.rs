@@ -0,0 +1,139 @@
+//! Relayer state persisted across blueprint restarts
+
+use crate::history::{sync_dir, write_synced};
+use blueprint_sdk as sdk;
+use color_eyre::Result;
+use color_eyre::eyre::WrapErr;
+use sdk::alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Everything about the relayer that needs to survive a restart of the blueprint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayerState {
+    /// The ID of the container we last started
+    pub container_id: Option<String>,
+    /// When the container was started, in seconds since the UNIX epoch
+    pub started_at: Option<u64>,
+    /// Whether the relayer was stopped with the `stop_relayer` job
+    pub stopped: bool,
+    /// The config version the relayer runs with, see [`ConfigHistory`](crate::history::ConfigHistory)
+    pub active_version: Option<u64>,
+    /// The config version to fall back to if the active one fails
+    pub previous_version: Option<u64>,
+    /// The address of the keystore's ECDSA key the relayer signs with, the first one if `None`
+    pub signer: Option<Address>,
+}
+
+impl RelayerState {
+    /// Record a newly started container
+    pub fn set_container(&mut self, container_id: String) {
+        self.container_id = Some(container_id);
+        self.started_at = Some(unix_timestamp());
+    }
+
+    pub fn clear_container(&mut self) {
+        self.container_id = None;
+        self.started_at = None;
+    }
+
+    /// Switch to a new config version, keeping the current one to fall back to
+    pub fn activate(&mut self, version: u64) {
+        if self.active_version != Some(version) {
+            self.previous_version = self.active_version;
+        }
+        self.active_version = Some(version);
+    }
+
+    /// Whether a relayer should be running, which is only the case once configs were applied, and
+    /// until it's stopped
+    ///
+    /// On startup, the blueprint only adopts or starts a relayer if this holds.
+    pub fn should_run(&self) -> bool {
+        !self.stopped && self.active_version.is_some()
+    }
+
+    /// How long the container has been running, if there is one
+    pub fn uptime_secs(&self) -> Option<u64> {
+        self.started_at
+            .map(|started_at| unix_timestamp().saturating_sub(started_at))
+    }
+}
+
+/// The current time, in seconds since the UNIX epoch
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// A file backed store for the [`RelayerState`]
+///
+/// The state is written to a temporary file and renamed over the old one, like the
+/// [`Journal`](crate::journal::Journal), so a crash part way through a write leaves the previous
+/// state intact.
+#[derive(Debug)]
+pub struct StateStore {
+    path: PathBuf,
+    state: Mutex<RelayerState>,
+}
+
+impl StateStore {
+    /// Open the store at `path`, starting from the default state if it doesn't exist
+    ///
+    /// # Errors
+    ///
+    /// * Unable to read the file at `path`
+    /// * The file is corrupt
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            let contents = std::fs::read(&path)?;
+            serde_json::from_slice(&contents)
+                .wrap_err_with(|| format!("Relayer state `{}` is corrupt", path.display()))?
+        } else {
+            RelayerState::default()
+        };
+
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn get(&self) -> RelayerState {
+        self.lock().clone()
+    }
+
+    /// Modify the state and persist it
+    ///
+    /// The state is only changed once it's on disk.
+    ///
+    /// # Errors
+    ///
+    /// * Unable to write the state to disk
+    pub fn update(&self, f: impl FnOnce(&mut RelayerState)) -> Result<()> {
+        let mut guard = self.lock();
+        let mut state = guard.clone();
+        f(&mut state);
+
+        let tmp = self.path.with_extension("tmp");
+        write_synced(&tmp, &serde_json::to_vec(&state)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        if let Some(parent) = self.path.parent() {
+            sync_dir(parent)?;
+        }
+
+        *guard = state;
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RelayerState> {
+        // The state is only replaced whole, so it's never left inconsistent by a panic
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
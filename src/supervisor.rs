@@ -0,0 +1,181 @@
+//! Background supervision of the relayer container
+
+use crate::HyperlaneContext;
+use blueprint_sdk as sdk;
+use docktopus::bollard::models::EventMessage;
+use docktopus::bollard::system::EventsOptions;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+/// Delay before resubscribing to Docker events after the stream ends
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Settings for the container supervisor
+#[derive(Debug, Clone)]
+pub struct SupervisorSettings {
+    /// Consecutive crashes tolerated before falling back to the previous configs
+    pub max_restarts: u32,
+    /// The delay before the first restart, doubled for every consecutive crash
+    pub backoff_initial: Duration,
+    /// The upper bound for the restart delay
+    pub backoff_max: Duration,
+    /// How long the relayer has to stay up for its crash count to reset
+    pub stable_period: Duration,
+}
+
+impl Default for SupervisorSettings {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(300),
+            stable_period: Duration::from_secs(600),
+        }
+    }
+}
+
+impl SupervisorSettings {
+    /// The delay before restart `attempt`, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.backoff_initial
+            .saturating_mul(factor)
+            .min(self.backoff_max)
+    }
+}
+
+/// Watch Docker for our container dying, and bring it back up
+///
+/// Only the container currently tracked in the [`HyperlaneContext`] is supervised, so containers
+/// removed on purpose (by a job or on shutdown) are left alone.
+pub(crate) async fn supervise(ctx: HyperlaneContext) {
+    let mut consecutive_crashes = 0;
+    let mut last_crash = Instant::now();
+
+    loop {
+        let mut filters = HashMap::new();
+        filters.insert("type", vec!["container"]);
+        filters.insert("event", vec!["die"]);
+
+        let mut events = ctx.connection.events(Some(EventsOptions {
+            filters,
+            ..Default::default()
+        }));
+
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    sdk::warn!("Docker event stream failed: {e}");
+                    break;
+                }
+            };
+
+            let Some(id) = event.actor.as_ref().and_then(|actor| actor.id.clone()) else {
+                continue;
+            };
+
+            {
+                let _lifecycle = ctx.lifecycle.lock().await;
+                if !ctx.is_tracked(&id).await {
+                    continue;
+                }
+            }
+
+            sdk::error!("Relayer container died (exit code: {})", exit_code(&event));
+
+            if last_crash.elapsed() >= ctx.settings.supervisor.stable_period {
+                consecutive_crashes = 0;
+            }
+            last_crash = Instant::now();
+
+            recover(&ctx, &id, &mut consecutive_crashes).await;
+        }
+
+        sdk::tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+async fn recover(ctx: &HyperlaneContext, id: &str, consecutive_crashes: &mut u32) {
+    let settings = &ctx.settings.supervisor;
+    let version = ctx.state.get().active_version;
+    // `None` once a restart fails before creating a container
+    let mut id = Some(id.to_string());
+    loop {
+        *consecutive_crashes += 1;
+
+        if *consecutive_crashes > settings.max_restarts {
+            let _lifecycle = ctx.lifecycle.lock().await;
+            if !is_down(ctx, id.as_deref(), version).await {
+                return;
+            }
+
+            sdk::error!(
+                "Relayer crashed {} times in a row, falling back to the previous configs",
+                settings.max_restarts
+            );
+            *consecutive_crashes = 0;
+            if let Err(e) = ctx.revert_configs().await {
+                sdk::error!("Failed to fall back to the previous configs: {e}");
+            }
+            return;
+        }
+
+        let delay = settings.backoff(*consecutive_crashes);
+        sdk::info!("Restarting relayer in {delay:?} (attempt {consecutive_crashes})");
+        sdk::tokio::time::sleep(delay).await;
+
+        let _lifecycle = ctx.lifecycle.lock().await;
+
+        // A job may have replaced the container in the meantime
+        if !is_down(ctx, id.as_deref(), version).await {
+            return;
+        }
+
+        if let Err(e) = ctx.remove_existing_container().await {
+            sdk::error!("Failed to remove dead relayer container: {e}");
+        }
+
+        match ctx.spinup_container().await {
+            Ok(()) => {
+                let restarts = ctx.restarts.fetch_add(1, Ordering::Relaxed) + 1;
+                sdk::info!("Relayer restarted ({restarts} restarts total)");
+                return;
+            }
+            Err(e) => {
+                sdk::error!("Failed to restart relayer: {e}");
+
+                // The failed container is still tracked, and is the one to clean up next. If
+                // none was created, no `die` event will follow, so keep trying from here.
+                id = ctx.container.lock().await.clone();
+            }
+        }
+    }
+}
+
+/// Whether the relayer is still down, and the supervisor's to bring back up
+///
+/// `id` is the container that died, or `None` if the last restart failed before creating one.
+/// In that case, a job that started, stopped, or switched the relayer's configs takes over.
+async fn is_down(ctx: &HyperlaneContext, id: Option<&str>, version: Option<u64>) -> bool {
+    match id {
+        Some(id) => ctx.is_tracked(id).await,
+        None => {
+            let state = ctx.state.get();
+            ctx.container.lock().await.is_none()
+                && !state.stopped
+                && state.active_version == version
+        }
+    }
+}
+
+fn exit_code(event: &EventMessage) -> &str {
+    event
+        .actor
+        .as_ref()
+        .and_then(|actor| actor.attributes.as_ref())
+        .and_then(|attributes| attributes.get("exitCode"))
+        .map_or("unknown", String::as_str)
+}
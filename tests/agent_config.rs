@@ -0,0 +1,178 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::agent_config::{self, Protocol};
+use blueprint::registry;
+use blueprint::registry::layout::{self, RegistryChain};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const TEST_ASSETS_PATH: &str = "./tests/assets";
+
+/// The agent config for the test registry in `tests/assets`
+fn agent_config() -> String {
+    let chains = [
+        ("testnet1", "127.0.0.1:8545"),
+        ("testnet2", "127.0.0.1:8546"),
+    ];
+
+    let mut registry = BTreeMap::new();
+    for (chain, rpc_url) in chains {
+        let assets = Path::new(TEST_ASSETS_PATH);
+        let metadata =
+            std::fs::read_to_string(assets.join(format!("{chain}-metadata.yaml.template")))
+                .unwrap()
+                .replace("{RPC_URL}", rpc_url);
+        let addresses = std::fs::read(assets.join(format!("{chain}-addresses.yaml"))).unwrap();
+
+        let registry_chain: &mut RegistryChain = registry.entry(chain.to_string()).or_default();
+        registry_chain.insert(
+            layout::METADATA_FILE,
+            layout::parse_file(chain, layout::METADATA_FILE, metadata.as_bytes()).unwrap(),
+        );
+        registry_chain.insert(
+            layout::ADDRESSES_FILE,
+            layout::parse_file(chain, layout::ADDRESSES_FILE, &addresses).unwrap(),
+        );
+    }
+
+    let chains = chains.map(|(chain, _)| chain.to_string());
+    layout::build_agent_config(&chains, &registry).unwrap()
+}
+
+#[test]
+fn accepts_valid_config() {
+    let config = agent_config::validate(&[agent_config()]).unwrap();
+    assert_eq!(config.chains.len(), 2);
+    assert_eq!(config.chains["testnet1"].domain_id, Some(31337));
+    assert_eq!(config.chains["testnet2"].protocol, Some(Protocol::Ethereum));
+}
+
+#[test]
+fn merges_configs_in_order() {
+    let overlay =
+        String::from(r#"{"chains": {"testnet1": {"rpcUrls": [{"http": "https://rpc.example"}]}}}"#);
+    let config = agent_config::validate(&[agent_config(), overlay]).unwrap();
+
+    let rpc_urls = config.chains["testnet1"].rpc_urls.as_ref().unwrap();
+    assert_eq!(rpc_urls.len(), 1);
+    assert_eq!(rpc_urls[0].http, "https://rpc.example");
+    // Untouched fields survive the merge
+    assert_eq!(config.chains["testnet1"].domain_id, Some(31337));
+}
+
+#[test]
+fn reports_every_problem() {
+    let bad = String::from(
+        r#"{
+            "chains": {
+                "testnet1": {"mailbox": "0x1234", "domainId": 31338},
+                "testnet3": {"domainId": "not a number"}
+            }
+        }"#,
+    );
+    let err = agent_config::validate(&[agent_config(), bad, String::from("{")]).unwrap_err();
+
+    let expected = [
+        "config 2:",
+        "chains.testnet3:",
+        "chains.testnet1: `mailbox` is not a valid ethereum address",
+        "domain ID 31338 is used by multiple chains: testnet1, testnet2",
+    ];
+    for expected in expected {
+        assert!(
+            err.problems
+                .iter()
+                .any(|problem| problem.starts_with(expected)),
+            "missing `{expected}` in {err}"
+        );
+    }
+}
+
+#[test]
+fn reports_missing_fields() {
+    let config = String::from(r#"{"chains": {"testnet3": {"protocol": "ethereum"}}}"#);
+    let err = agent_config::validate(&[config]).unwrap_err();
+
+    for field in [
+        "domainId",
+        "rpcUrls",
+        "mailbox",
+        "merkleTreeHook",
+        "validatorAnnounce",
+        "interchainGasPaymaster",
+        "index.from",
+    ] {
+        let expected = format!("chains.testnet3: missing `{field}`");
+        assert!(err.problems.contains(&expected), "missing `{expected}`");
+    }
+}
+
+#[test]
+fn parses_relay_chains() {
+    let chains = agent_config::parse_relay_chains(" testnet1, testnet2 ,testnet1").unwrap();
+    assert_eq!(chains, ["testnet1", "testnet2"]);
+
+    assert!(agent_config::parse_relay_chains("testnet1,").is_err());
+    assert!(agent_config::parse_relay_chains("testnet1,testnet1").is_err());
+    assert!(agent_config::parse_relay_chains("").is_err());
+}
+
+#[test]
+fn rejects_unknown_relay_chains() {
+    let config = agent_config::validate(&[agent_config()]).unwrap();
+
+    let known = agent_config::parse_relay_chains("testnet1,testnet2,ethereum").unwrap();
+    config.check_relay_chains(&known).unwrap();
+
+    let unknown = agent_config::parse_relay_chains("testnet1,typo,ethereum,tpyo").unwrap();
+    let err = config.check_relay_chains(&unknown).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Unknown relay chains: typo, tpyo.")
+    );
+}
+
+#[test]
+fn allows_partial_default_chains() {
+    let config =
+        String::from(r#"{"chains": {"ethereum": {"rpcUrls": [{"http": "https://rpc.example"}]}}}"#);
+    agent_config::validate(&[config]).unwrap();
+
+    let bad_address = String::from(r#"{"chains": {"ethereum": {"mailbox": "not an address"}}}"#);
+    let err = agent_config::validate(&[bad_address]).unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+}
+
+#[test]
+fn renders_effective_config() {
+    let relay_chains = agent_config::parse_relay_chains("ethereum,osmosis").unwrap();
+
+    // Without any configs, the image's configs of the relay chains are used as is
+    let rendered: Value =
+        serde_json::from_str(&agent_config::render(&[], &relay_chains).unwrap()).unwrap();
+    for chain in &relay_chains {
+        let mut expected = registry::default_chain(chain).unwrap().config.clone();
+        expected.insert(String::from("name"), Value::from(chain.as_str()));
+        assert_eq!(
+            rendered["chains"][chain],
+            Value::Object(expected),
+            "{chain}"
+        );
+    }
+    assert_eq!(rendered["chains"]["osmosis"]["bech32Prefix"], "osmo");
+    assert_eq!(rendered["relayChains"], "ethereum,osmosis");
+
+    let rpc = String::from(
+        r#"{"chains": {"ethereum": {"rpcUrls": [{"http": "https://rpc.example"}], "signer": {"type": "hexKey"}}}}"#,
+    );
+    let rendered: Value =
+        serde_json::from_str(&agent_config::render(&[rpc], &relay_chains).unwrap()).unwrap();
+    let ethereum = &rendered["chains"]["ethereum"];
+    assert_eq!(ethereum["domainId"], 1);
+    assert_eq!(
+        ethereum["rpcUrls"],
+        json!([{ "http": "https://rpc.example" }])
+    );
+    assert!(ethereum.get("signer").is_none());
+}
@@ -0,0 +1,91 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::SignerBalance;
+use blueprint::agent_config::RpcUrl;
+use blueprint::balances::{self, BalanceSettings, RpcHttpClient};
+use blueprint_sdk as sdk;
+use sdk::alloy::primitives::{Address, U256};
+use sdk::tangle::extract::List;
+use sdk::tangle::serde::to_field;
+use sdk::tokio;
+use sdk::tangle_subxt::FieldExt;
+use sdk::tangle_subxt::tangle_testnet_runtime::api::runtime_types::tangle_primitives::services::field::FieldType;
+use std::time::Duration;
+
+fn rpc_url(http: &str) -> RpcUrl {
+    RpcUrl {
+        http: http.to_string(),
+        other: Default::default(),
+    }
+}
+
+#[test]
+fn per_chain_min_balances() {
+    let mut settings = BalanceSettings::default();
+    settings
+        .min_balances
+        .insert(String::from("testnet1"), U256::from(5));
+
+    assert_eq!(settings.min_balance("testnet1"), U256::from(5));
+    assert_eq!(settings.min_balance("testnet2"), settings.min_balance);
+}
+
+#[test]
+fn result_type_describes_entries() {
+    let field_type = to_field(List::<SignerBalance>::default())
+        .unwrap()
+        .field_type();
+    let FieldType::List(entry) = field_type else {
+        panic!("expected a list, got {field_type:?}");
+    };
+    assert!(matches!(*entry, FieldType::Struct(..)), "got {entry:?}");
+}
+
+#[tokio::test]
+async fn reports_unreachable_rpcs() {
+    let client = RpcHttpClient::new(true).unwrap();
+    let timeout = Duration::from_secs(1);
+
+    let err = balances::query_balance(&client, &[], Address::ZERO, timeout)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No RPC URLs"));
+
+    // Every URL is tried, and the last failure is reported
+    let rpc_urls = [rpc_url("not a url"), rpc_url("http://127.0.0.1:1")];
+    let err = balances::query_balance(&client, &rpc_urls, Address::ZERO, timeout)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("http://127.0.0.1:1"), "{err}");
+}
+
+#[tokio::test]
+async fn refuses_non_public_rpcs() {
+    let client = RpcHttpClient::new(false).unwrap();
+    let timeout = Duration::from_secs(1);
+
+    for url in [
+        "http://127.0.0.1:1",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]:1",
+    ] {
+        let err = balances::query_balance(&client, &[rpc_url(url)], Address::ZERO, timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("non-public"), "{url}: {err}");
+    }
+
+    // Host names are checked as they're resolved
+    let err = balances::query_balance(
+        &client,
+        &[rpc_url("http://localhost:1")],
+        Address::ZERO,
+        timeout,
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("does not resolve to a public address"),
+        "{err:?}"
+    );
+}
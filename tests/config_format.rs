@@ -0,0 +1,109 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::config_format::{self, ConfigFormat};
+use serde_json::{Value, json};
+
+#[test]
+fn detects_formats() {
+    for (content_type, path, format) in [
+        (
+            Some("application/json"),
+            "/config",
+            Some(ConfigFormat::Json),
+        ),
+        (
+            Some("application/json; charset=utf-8"),
+            "/config.yaml",
+            Some(ConfigFormat::Json),
+        ),
+        (
+            Some("application/ld+json"),
+            "/config",
+            Some(ConfigFormat::Json),
+        ),
+        (
+            Some("application/yaml"),
+            "/config.json",
+            Some(ConfigFormat::Yaml),
+        ),
+        (Some("Text/YAML"), "/config", Some(ConfigFormat::Yaml)),
+        // Raw file hosts often serve everything as text
+        (
+            Some("text/plain"),
+            "/chains/testnet1/metadata.yaml",
+            Some(ConfigFormat::Yaml),
+        ),
+        (None, "/config.YML", Some(ConfigFormat::Yaml)),
+        (None, "/config.json", Some(ConfigFormat::Json)),
+        (None, "", None),
+        (Some("application/octet-stream"), "/config", None),
+    ] {
+        assert_eq!(
+            ConfigFormat::detect(content_type, path),
+            format,
+            "{content_type:?} {path}"
+        );
+    }
+}
+
+#[test]
+fn converts_yaml_to_json() {
+    let yaml = r#"
+chains:
+  testnet1:
+    domainId: 31337
+    protocol: ethereum
+    mailbox: "0x0b48aF34f4c854F5ae1A3D587da471FeA45bAD52"
+    rpcUrls:
+      - http: http://localhost:8545
+relayChains: testnet1
+"#;
+    let expected = json!({
+        "chains": {
+            "testnet1": {
+                "domainId": 31337,
+                "protocol": "ethereum",
+                "mailbox": "0x0b48aF34f4c854F5ae1A3D587da471FeA45bAD52",
+                "rpcUrls": [{ "http": "http://localhost:8545" }],
+            }
+        },
+        "relayChains": "testnet1",
+    });
+
+    for format in [Some(ConfigFormat::Yaml), None] {
+        let config = config_format::to_json(yaml.to_string(), format).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&config).unwrap(), expected);
+    }
+
+    // JSON is kept as is
+    let config = r#"{"chains": {}}"#;
+    for format in [Some(ConfigFormat::Json), Some(ConfigFormat::Yaml), None] {
+        let converted = config_format::to_json(config.to_string(), format).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&converted).unwrap(),
+            json!({"chains": {}})
+        );
+    }
+    assert_eq!(
+        config_format::to_json(config.to_string(), None).unwrap(),
+        config
+    );
+}
+
+#[test]
+fn rejects_other_content() {
+    for (config, format) in [
+        ("chains: {", None),
+        ("just some text", None),
+        ("<html></html>", None),
+        ("[1, 2, 3]", None),
+        ("- a\n- b", Some(ConfigFormat::Yaml)),
+        ("chains: {}", Some(ConfigFormat::Json)),
+        ("", None),
+    ] {
+        assert!(
+            config_format::to_json(config.to_string(), format).is_err(),
+            "{config:?}"
+        );
+    }
+}
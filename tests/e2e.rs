@@ -1,13 +1,19 @@
 use blueprint::HyperlaneContext;
+use blueprint::fetch::FetchSettings;
+use blueprint::settings::Settings;
+use blueprint::signer::HexKey;
 use blueprint_sdk as sdk;
 use color_eyre::Report;
-use docktopus::bollard::container::RemoveContainerOptions;
+use docktopus::bollard::container::{InspectContainerOptions, RemoveContainerOptions};
 use docktopus::bollard::network::{
     ConnectNetworkOptions, CreateNetworkOptions, InspectNetworkOptions,
 };
 use docktopus::{DockerBuilder, bollard};
 use hyperlane_relayer_blueprint_lib as blueprint;
 use sdk::Job;
+use sdk::extract::Context;
+use sdk::keystore::Keystore;
+use sdk::tangle::extract::TangleArg;
 use sdk::tangle::layers::TangleLayer;
 use sdk::tangle::serde::to_field;
 use sdk::tangle_subxt::tangle_testnet_runtime::api::services::calls::types::call::Args;
@@ -23,7 +29,6 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, LazyLock};
 
-const AGENT_CONFIG_TEMPLATE_PATH: &str = "./tests/assets/agent-config.json.template";
 const CORE_CONFIG_PATH: &str = "./tests/assets/core-config.yaml";
 const TEST_ASSETS_PATH: &str = "./tests/assets";
 
@@ -35,28 +40,38 @@ fn setup_temp_dir(
 
     let tempdir = tempfile::tempdir().unwrap();
 
-    // Create the registry
-    let registry_path = tempdir.path().join("chains");
-    fs::create_dir(&registry_path).unwrap();
-
-    for (prefix, rpc_url) in FILE_PREFIXES
-        .iter()
-        .zip([&*testnet1_host_rpc_url, &*testnet2_host_rpc_url])
-    {
-        let testnet_path = registry_path.join(prefix);
-        fs::create_dir(&testnet_path).unwrap();
-
-        let addresses_path = Path::new(TEST_ASSETS_PATH).join(format!("{prefix}-addresses.yaml"));
-        fs::copy(addresses_path, testnet_path.join("addresses.yaml")).unwrap();
-
-        let metadata_template_path =
-            Path::new(TEST_ASSETS_PATH).join(format!("{prefix}-metadata.yaml.template"));
-        let testnet1_metadata = fs::read_to_string(metadata_template_path).unwrap();
-        fs::write(
-            testnet_path.join("metadata.yaml"),
-            testnet1_metadata.replace("{RPC_URL}", rpc_url),
-        )
-        .unwrap();
+    // Create the registries, one for the CLI in the root, and one the relayer's config is built
+    // from
+    let registries = [
+        (
+            tempdir.path().join("chains"),
+            [&*testnet1_host_rpc_url, &*testnet2_host_rpc_url],
+        ),
+        (
+            tempdir.path().join("relayer-registry").join("chains"),
+            [&*testnet1_docker_rpc_url, &*testnet2_docker_rpc_url],
+        ),
+    ];
+    for (registry_path, rpc_urls) in registries {
+        fs::create_dir_all(&registry_path).unwrap();
+
+        for (prefix, rpc_url) in FILE_PREFIXES.iter().zip(rpc_urls) {
+            let testnet_path = registry_path.join(prefix);
+            fs::create_dir(&testnet_path).unwrap();
+
+            let addresses_path =
+                Path::new(TEST_ASSETS_PATH).join(format!("{prefix}-addresses.yaml"));
+            fs::copy(addresses_path, testnet_path.join("addresses.yaml")).unwrap();
+
+            let metadata_template_path =
+                Path::new(TEST_ASSETS_PATH).join(format!("{prefix}-metadata.yaml.template"));
+            let metadata = fs::read_to_string(metadata_template_path).unwrap();
+            fs::write(
+                testnet_path.join("metadata.yaml"),
+                metadata.replace("{RPC_URL}", rpc_url),
+            )
+            .unwrap();
+        }
     }
 
     // Create the core config
@@ -64,16 +79,6 @@ fn setup_temp_dir(
     fs::create_dir(&configs_dir).unwrap();
     fs::copy(CORE_CONFIG_PATH, configs_dir.join("core-config.yaml")).unwrap();
 
-    // Create agent config
-    let agent_config_template = fs::read_to_string(AGENT_CONFIG_TEMPLATE_PATH).unwrap();
-    fs::write(
-        tempdir.path().join("agent-config.json"),
-        agent_config_template
-            .replace("{TESTNET_1_RPC}", &testnet1_docker_rpc_url)
-            .replace("{TESTNET_2_RPC}", &testnet2_docker_rpc_url),
-    )
-    .unwrap();
-
     tempdir
 }
 
@@ -218,8 +223,8 @@ async fn relayer_test_inner() -> color_eyre::Result<()> {
     // When it comes time to relay the message, the command is run outside the Docker network,
     // so we need to get both addresses.
     //
-    // The internal address is written to `agent-config.json`.
-    // The host addresses are written to `testnet{1,2}-metadata.yaml`.
+    // The internal addresses are written to the relayer's registry.
+    // The host addresses are written to the CLI's registry.
     let testnet1_docker_rpc_url = format!("{}:8545", origin.ip);
     let testnet2_docker_rpc_url = format!("{}:8545", dest.ip);
 
@@ -251,14 +256,25 @@ async fn relayer_test_inner() -> color_eyre::Result<()> {
         .add_job(blueprint::set_config.layer(TangleLayer))
         .await;
 
-    let ctx = Arc::new(HyperlaneContext::new(harness.env().clone(), temp_dir_path.clone()).await?);
-    test_env.start(ctx).await?;
+    // The anvil RPCs are on the Docker network's private addresses
+    let settings = Settings {
+        fetch: FetchSettings {
+            allow_private_ips: true,
+            ..FetchSettings::default()
+        },
+        ..Settings::default()
+    };
+    let ctx = Arc::new(
+        HyperlaneContext::with_settings(harness.env().clone(), temp_dir_path.clone(), settings)
+            .await?,
+    );
+    test_env.start(ctx.clone()).await?;
 
     // Pass the arguments
-    let agent_config_path = std::path::absolute(temp_dir_path.join("agent-config.json"))?;
+    let registry_path = std::path::absolute(temp_dir_path.join("relayer-registry"))?;
     let config_urls = to_field(Some(vec![format!(
-        "file://{}",
-        agent_config_path.display()
+        "registry+file://{}",
+        registry_path.display()
     )]))?;
     let relay_chains = to_field(String::from("testnet1,testnet2"))?;
 
@@ -272,6 +288,20 @@ async fn relayer_test_inner() -> color_eyre::Result<()> {
 
     assert_eq!(results.service_id, service_id);
 
+    assert_signer_key_hidden(&harness.env().keystore(), &ctx).await?;
+
+    // The signer is funded on both anvil testnets
+    let balances = ctx.signer_balances().await?;
+    let chains = balances
+        .iter()
+        .map(|balance| balance.chain.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(chains, ["testnet1", "testnet2"]);
+    for balance in &balances {
+        assert!(balance.error.is_empty(), "{balance:?}");
+        assert!(!balance.low, "{balance:?}");
+    }
+
     // The relayer is now running, send a message
     std::env::set_current_dir(temp_dir_path)?;
     let send_msg_output = Command::new(&*HYPERLANE_CLI_PATH)
@@ -369,5 +399,66 @@ async fn relayer_test_inner() -> color_eyre::Result<()> {
         return Err(Report::msg("Message was not delivered"));
     }
 
+    // An unknown version is refused without touching the relayer
+    let err = blueprint::rollback_config(Context(ctx.clone()), TangleArg(99))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+    assert_eq!(ctx.active_config_version(), Some(1));
+
+    // Rolling back to the active version restarts the relayer with it
+    blueprint::rollback_config(Context(ctx.clone()), TangleArg(1)).await?;
+    let status = ctx.status().await?;
+    assert_eq!(status.state, blueprint::RunState::Running);
+    assert_eq!(status.config_version, 1);
+
+    // Rotate to a fresh key, moving the old signer's funds over
+    let rotated = ctx.rotate_signer(None, true).await?;
+    assert_eq!(rotated.swept, "testnet1,testnet2");
+    assert!(rotated.sweep_failed.is_empty());
+    for balance in ctx.signer_balances().await? {
+        assert_eq!(balance.address, rotated.address);
+        assert!(!balance.low, "{balance:?}");
+    }
+
+    Ok(())
+}
+
+/// The signer key must never be visible in `docker inspect`
+async fn assert_signer_key_hidden(
+    keystore: &Keystore,
+    ctx: &HyperlaneContext,
+) -> color_eyre::Result<()> {
+    let key = HexKey::from_keystore(keystore, None)?;
+    let key = key.expose();
+    let unprefixed_key = key.trim_start_matches("0x");
+
+    let container_id = ctx
+        .container_id()
+        .await
+        .expect("the relayer should be running");
+    let connection = DockerBuilder::new().await?;
+    let inspect = connection
+        .inspect_container(&container_id, None::<InspectContainerOptions>)
+        .await?;
+
+    let config = inspect.config.unwrap_or_default();
+    let visible = [
+        inspect.args.unwrap_or_default(),
+        config.cmd.unwrap_or_default(),
+        config.entrypoint.unwrap_or_default(),
+        config.env.unwrap_or_default(),
+    ];
+    for value in visible.iter().flatten() {
+        assert!(
+            !value.contains(unprefixed_key),
+            "signer key found in the container config: `{value}`"
+        );
+        assert!(
+            !value.contains("defaultSigner.key"),
+            "signer key argument found in the container config"
+        );
+    }
+
     Ok(())
 }
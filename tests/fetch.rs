@@ -0,0 +1,430 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::fetch::{self, ConfigFetcher, ConfigUrl, ContentHash, FetchSettings};
+use blueprint_sdk as sdk;
+use cid::Cid;
+use cid::multihash::Multihash;
+use sdk::testing::tempfile;
+use sdk::tokio;
+use sha2::Digest;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const EMPTY_SHA256: &str = "0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+const EMPTY_KECCAK256: &str = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+#[test]
+fn hashes_contents() {
+    assert_eq!(
+        ContentHash::sha256(b"").to_string(),
+        format!("sha256={EMPTY_SHA256}")
+    );
+    assert_eq!(
+        ContentHash::keccak256(b"").to_string(),
+        format!("keccak256={EMPTY_KECCAK256}")
+    );
+
+    let pin = format!("sha256={EMPTY_SHA256}")
+        .parse::<ContentHash>()
+        .unwrap();
+    pin.verify(b"").unwrap();
+    assert!(pin.verify(b"{}").is_err());
+}
+
+#[test]
+fn parses_pinned_urls() {
+    let url = format!("https://example.com/config.json#keccak256={EMPTY_KECCAK256}")
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert_eq!(url.url.as_str(), "https://example.com/config.json");
+    assert_eq!(url.pin, Some(ContentHash::keccak256(b"")));
+
+    // The `0x` prefix is optional
+    let unprefixed = EMPTY_SHA256.trim_start_matches("0x");
+    let url = format!("https://example.com/config.json#sha256={unprefixed}")
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert_eq!(url.pin, Some(ContentHash::sha256(b"")));
+
+    // Other fragments aren't pins
+    let url = "https://example.com/config.json#section"
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert_eq!(url.url.fragment(), Some("section"));
+    assert_eq!(url.pin, None);
+
+    for bad in [
+        "https://example.com/config.json#md5=abcd",
+        "https://example.com/config.json#sha256=abcd",
+        "not a url",
+    ] {
+        assert!(bad.parse::<ConfigUrl>().is_err(), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn checks_fetched_configs_against_pins() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let config = r#"{"chains": {}}"#;
+    std::fs::write(&path, config).unwrap();
+
+    let file_url = format!("file://{}", path.display());
+    let pin = ContentHash::sha256(config.as_bytes());
+
+    let pinned = format!("{file_url}#{pin}").parse::<ConfigUrl>().unwrap();
+    let fetcher = ConfigFetcher::new(FetchSettings::default(), true).unwrap();
+    assert_eq!(fetcher.fetch(&pinned).await.unwrap(), config);
+
+    let wrong_pin = format!("{file_url}#sha256={EMPTY_SHA256}")
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert!(fetcher.fetch(&wrong_pin).await.is_err());
+}
+
+/// Serve `response` to every connection on a local port, after `delay`
+async fn serve(response: Vec<u8>, delay: Duration) -> String {
+    serve_sequence(vec![response], delay).await
+}
+
+/// Serve `responses` to consecutive connections on a local port, repeating the last one
+async fn serve_sequence(responses: Vec<Vec<u8>>, delay: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        for i in 0.. {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let response = responses[i.min(responses.len() - 1)].clone();
+            tokio::spawn(async move {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                tokio::time::sleep(delay).await;
+                let _ = stream.write_all(&response).await;
+            });
+        }
+    });
+
+    format!("http://{addr}/config.json")
+}
+
+fn ok_response(body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .into_bytes()
+}
+
+fn status_response(status: &str) -> Vec<u8> {
+    format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").into_bytes()
+}
+
+fn local_fetcher(settings: FetchSettings) -> ConfigFetcher {
+    let settings = FetchSettings {
+        allow_http: true,
+        allow_private_ips: true,
+        ..settings
+    };
+    ConfigFetcher::new(settings, false).unwrap()
+}
+
+#[test]
+fn classifies_public_addresses() {
+    for public in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+        assert!(fetch::is_public(public.parse().unwrap()), "{public}");
+    }
+    for private in [
+        "127.0.0.1",
+        "10.0.0.1",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!fetch::is_public(private.parse().unwrap()), "{private}");
+    }
+}
+
+#[tokio::test]
+async fn rejects_disallowed_urls() {
+    let fetcher = ConfigFetcher::new(FetchSettings::default(), false).unwrap();
+    for url in [
+        "http://example.com/config.json",
+        "file:///etc/passwd",
+        "ftp://example.com/config.json",
+        "https://127.0.0.1/config.json",
+        "https://[::1]/config.json",
+        "https://169.254.169.254/latest/meta-data",
+        "https://localhost/config.json",
+    ] {
+        let url = url.parse::<ConfigUrl>().unwrap();
+        assert!(fetcher.fetch(&url).await.is_err(), "{}", url.url);
+    }
+
+    let too_many = vec![
+        "https://example.com/config.json"
+            .parse::<ConfigUrl>()
+            .unwrap();
+        17
+    ];
+    let err = fetcher.fetch_all(&too_many, &[]).await.unwrap_err();
+    assert!(err.to_string().contains("Too many config URLs"), "{err}");
+}
+
+#[tokio::test]
+async fn enforces_fetch_limits() {
+    let config = r#"{"chains": {}}"#;
+    let url = serve(ok_response(config), Duration::ZERO)
+        .await
+        .parse::<ConfigUrl>()
+        .unwrap();
+    let fetcher = local_fetcher(FetchSettings::default());
+    let fetched = fetcher
+        .fetch_all(std::slice::from_ref(&url), &[])
+        .await
+        .unwrap();
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].config, config);
+
+    // The body is capped even without a content length
+    let small = local_fetcher(FetchSettings {
+        max_body_size: config.len() - 1,
+        ..FetchSettings::default()
+    });
+    assert!(small.fetch(&url).await.is_err());
+    let unsized_response = format!("HTTP/1.1 200 OK\r\nconnection: close\r\n\r\n{config}");
+    let unsized_url = serve(unsized_response.into_bytes(), Duration::ZERO)
+        .await
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert!(small.fetch(&unsized_url).await.is_err());
+    fetcher.fetch(&unsized_url).await.unwrap();
+
+    let hanging = serve(Vec::new(), Duration::from_secs(60))
+        .await
+        .parse::<ConfigUrl>()
+        .unwrap();
+    let impatient = local_fetcher(FetchSettings {
+        request_timeout: Duration::from_millis(200),
+        retries: 0,
+        ..FetchSettings::default()
+    });
+    assert!(impatient.fetch(&hanging).await.is_err());
+
+    let redirect = format!(
+        "HTTP/1.1 302 Found\r\nlocation: {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        url.url
+    );
+    let redirected = serve(redirect.into_bytes(), Duration::ZERO)
+        .await
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert_eq!(fetcher.fetch(&redirected).await.unwrap(), config);
+    let no_redirects = local_fetcher(FetchSettings {
+        max_redirects: 0,
+        ..FetchSettings::default()
+    });
+    assert!(no_redirects.fetch(&redirected).await.is_err());
+}
+
+#[tokio::test]
+async fn fetches_in_parallel_in_order() {
+    let settings = FetchSettings {
+        retry_backoff: Duration::from_millis(10),
+        ..FetchSettings::default()
+    };
+    let fetcher = local_fetcher(settings.clone());
+
+    // The first config is the slowest, and the others don't wait for it
+    let mut urls = Vec::new();
+    let mut expected = Vec::new();
+    for (i, delay) in [300, 0, 100].into_iter().enumerate() {
+        let config = format!(r#"{{"chains": {{}}, "index": {i}}}"#);
+        let url = serve(ok_response(&config), Duration::from_millis(delay)).await;
+        urls.push(url.parse::<ConfigUrl>().unwrap());
+        expected.push(config);
+    }
+    let start = std::time::Instant::now();
+    let configs = fetcher.fetch_all(&urls, &[]).await.unwrap();
+    assert_eq!(
+        configs
+            .into_iter()
+            .map(|fetched| fetched.config)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(start.elapsed() < Duration::from_millis(400));
+
+    // Transient errors are retried
+    let flaky = serve_sequence(
+        vec![
+            status_response("503 Service Unavailable"),
+            status_response("429 Too Many Requests"),
+            ok_response(&expected[0]),
+        ],
+        Duration::ZERO,
+    )
+    .await
+    .parse::<ConfigUrl>()
+    .unwrap();
+    assert_eq!(fetcher.fetch(&flaky).await.unwrap(), expected[0]);
+
+    let no_retries = local_fetcher(FetchSettings {
+        retries: 0,
+        ..settings
+    });
+    let flaky = serve_sequence(
+        vec![
+            status_response("503 Service Unavailable"),
+            ok_response(&expected[0]),
+        ],
+        Duration::ZERO,
+    )
+    .await
+    .parse::<ConfigUrl>()
+    .unwrap();
+    assert!(no_retries.fetch(&flaky).await.is_err());
+
+    // Others aren't
+    let missing = serve_sequence(
+        vec![status_response("404 Not Found"), ok_response(&expected[0])],
+        Duration::ZERO,
+    )
+    .await
+    .parse::<ConfigUrl>()
+    .unwrap();
+    assert!(fetcher.fetch(&missing).await.is_err());
+}
+
+#[tokio::test]
+async fn fetches_ipfs_configs_through_the_gateway() {
+    let config = r#"{"chains": {}}"#;
+    let digest = sha2::Sha256::digest(config.as_bytes());
+    let cid = Cid::new_v1(0x55, Multihash::wrap(0x12, &digest).unwrap());
+    let url = format!("ipfs://{cid}").parse::<ConfigUrl>().unwrap();
+
+    // Not configured
+    let fetcher = ConfigFetcher::new(FetchSettings::default(), false).unwrap();
+    assert!(fetcher.fetch(&url).await.is_err());
+
+    // A local gateway is allowed, even though other private addresses aren't
+    let gateway = serve(ok_response(config), Duration::ZERO).await;
+    let fetcher = ConfigFetcher::new(
+        FetchSettings {
+            ipfs_gateway: Some(gateway.parse().unwrap()),
+            ..FetchSettings::default()
+        },
+        false,
+    )
+    .unwrap();
+    assert_eq!(fetcher.fetch(&url).await.unwrap(), config);
+
+    // The gateway isn't trusted
+    let tampered = serve(ok_response(r#"{"chains": []}"#), Duration::ZERO).await;
+    let fetcher = ConfigFetcher::new(
+        FetchSettings {
+            ipfs_gateway: Some(tampered.parse().unwrap()),
+            ..FetchSettings::default()
+        },
+        false,
+    )
+    .unwrap();
+    assert!(fetcher.fetch(&url).await.is_err());
+}
+
+#[tokio::test]
+async fn reads_local_configs_from_the_config_dir() {
+    let root = tempfile::tempdir().unwrap();
+    let dir = root.path().join("configs");
+    std::fs::create_dir_all(dir.join("testnet")).unwrap();
+    let config = r#"{"chains": {}}"#;
+    std::fs::write(dir.join("testnet/config.json"), config).unwrap();
+    std::fs::write(root.path().join("secret.json"), "{}").unwrap();
+    std::os::unix::fs::symlink(root.path().join("secret.json"), dir.join("link.json")).unwrap();
+
+    let url = |url: String| url.parse::<ConfigUrl>().unwrap();
+    let inside = [
+        url("local:testnet/config.json".to_string()),
+        url("local:./testnet/../testnet/config.json".to_string()),
+        url(format!("file://{}/testnet/config.json", dir.display())),
+    ];
+    let outside = [
+        url("local:../secret.json".to_string()),
+        url("local:%2e%2e/secret.json".to_string()),
+        url(format!("local:{}/secret.json", root.path().display())),
+        url("local:link.json".to_string()),
+        url("local:testnet".to_string()),
+        url(format!("file://{}/secret.json", root.path().display())),
+        url(format!("file://{}/../secret.json", dir.display())),
+    ];
+
+    // Not configured
+    let fetcher = ConfigFetcher::new(FetchSettings::default(), false).unwrap();
+    for url in &inside {
+        assert!(fetcher.fetch(url).await.is_err(), "{}", url.url);
+    }
+
+    let settings = FetchSettings {
+        local_config_dir: Some(dir.clone()),
+        ..FetchSettings::default()
+    };
+    let fetcher = ConfigFetcher::new(settings.clone(), false).unwrap();
+    for url in &inside {
+        assert_eq!(fetcher.fetch(url).await.unwrap(), config, "{}", url.url);
+    }
+    for url in &outside {
+        assert!(fetcher.fetch(url).await.is_err(), "{}", url.url);
+    }
+
+    let small = ConfigFetcher::new(
+        FetchSettings {
+            max_body_size: config.len() - 1,
+            ..settings
+        },
+        false,
+    )
+    .unwrap();
+    assert!(small.fetch(&inside[0]).await.is_err());
+}
+
+#[tokio::test]
+async fn converts_yaml_configs() {
+    let yaml = "chains:\n  testnet1:\n    domainId: 31337\n";
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/yaml\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{yaml}",
+        yaml.len()
+    );
+    let url = serve(response.into_bytes(), Duration::ZERO)
+        .await
+        .parse::<ConfigUrl>()
+        .unwrap();
+    let fetcher = local_fetcher(FetchSettings::default());
+    let config = fetcher.fetch(&url).await.unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&config).unwrap(),
+        serde_json::json!({"chains": {"testnet1": {"domainId": 31337}}})
+    );
+
+    // Pins are checked against the config as published, and that's what is hashed
+    let pin = ContentHash::sha256(yaml.as_bytes());
+    let pinned = format!("{}#{pin}", url.url).parse::<ConfigUrl>().unwrap();
+    let fetched = fetcher.fetch_all(&[url, pinned], &[]).await.unwrap();
+    assert_eq!(
+        fetched[0].hash,
+        Some(ContentHash::keccak256(yaml.as_bytes()))
+    );
+    assert_eq!(fetched[1].hash, Some(pin));
+
+    let html = serve(ok_response("<html>Not found</html>"), Duration::ZERO)
+        .await
+        .replace("config.json", "config")
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert!(fetcher.fetch(&html).await.is_err());
+}
@@ -0,0 +1,155 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::fetch::ContentHash;
+use blueprint::history::{ConfigHistory, ConfigSource, Origin};
+use blueprint::state::RelayerState;
+use blueprint_sdk as sdk;
+use sdk::testing::tempfile;
+
+fn configs(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|index| format!(r#"{{"index": {index}}}"#))
+        .collect()
+}
+
+#[test]
+fn orders_configs_by_position() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = ConfigHistory::new(dir.path().to_path_buf(), 10);
+
+    let configs = configs(11);
+    let version = history
+        .create(&configs, "testnet1,testnet2", Origin::default())
+        .unwrap();
+
+    let files = history.config_files(version).unwrap();
+    let contents = files
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect::<Vec<_>>();
+    // `10.json` sorts before `2.json` by name, but is applied last
+    assert_eq!(contents, configs);
+    assert_eq!(files[10].file_name().unwrap(), "10.json");
+}
+
+#[test]
+fn prunes_oldest_versions_except_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = ConfigHistory::new(dir.path().to_path_buf(), 2);
+
+    for _ in 0..5 {
+        history
+            .create(&configs(1), "testnet1,testnet2", Origin::default())
+            .unwrap();
+    }
+    assert_eq!(history.versions().unwrap(), [1, 2, 3, 4, 5]);
+
+    // The active and previous versions survive, even when they're the oldest
+    history.prune(&[1, 5]).unwrap();
+    assert_eq!(history.versions().unwrap(), [1, 5]);
+
+    // Versions keep counting from the latest, never reusing a pruned one
+    let version = history
+        .create(&configs(1), "testnet1,testnet2", Origin::default())
+        .unwrap();
+    assert_eq!(version, 6);
+    assert!(!history.contains(2));
+}
+
+#[test]
+fn imports_legacy_configs() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path();
+    let history = ConfigHistory::new(data_dir.join("configs"), 10);
+
+    assert_eq!(history.import_legacy(data_dir).unwrap(), None);
+
+    std::fs::create_dir_all(data_dir.join("agent_configs")).unwrap();
+    std::fs::write(data_dir.join("agent_configs/0.json"), "{}").unwrap();
+    std::fs::write(data_dir.join("relay_chains.txt"), "testnet1,testnet2").unwrap();
+    std::fs::create_dir_all(data_dir.join("agent_configs.orig")).unwrap();
+    std::fs::write(data_dir.join("relay_chains.txt.orig"), "testnet1,testnet3").unwrap();
+
+    let version = history.import_legacy(data_dir).unwrap().unwrap();
+    assert_eq!(version, 1);
+    assert_eq!(history.config_files(version).unwrap().len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(history.relay_chains_path(version)).unwrap(),
+        "testnet1,testnet2"
+    );
+    assert!(history.meta(version).unwrap().sources.is_empty());
+
+    for legacy in [
+        "agent_configs",
+        "relay_chains.txt",
+        "agent_configs.orig",
+        "relay_chains.txt.orig",
+    ] {
+        assert!(!data_dir.join(legacy).exists(), "{legacy} was left behind");
+    }
+}
+
+#[test]
+fn activating_keeps_the_fallback() {
+    let mut state = RelayerState::default();
+
+    state.activate(1);
+    assert_eq!(
+        (state.active_version, state.previous_version),
+        (Some(1), None)
+    );
+
+    state.activate(2);
+    assert_eq!(
+        (state.active_version, state.previous_version),
+        (Some(2), Some(1))
+    );
+
+    // Reactivating the active version doesn't lose the fallback
+    state.activate(2);
+    assert_eq!(
+        (state.active_version, state.previous_version),
+        (Some(2), Some(1))
+    );
+}
+
+#[test]
+fn records_config_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = ConfigHistory::new(dir.path().to_path_buf(), 10);
+
+    let yaml = "index: 0\n";
+    let config = &configs(1)[0];
+    let source = ConfigSource::new(
+        Some(String::from("https://example.com/config.yaml")),
+        Some(ContentHash::keccak256(yaml.as_bytes())),
+        false,
+        config,
+    );
+    // The published contents are hashed, not the stored JSON
+    assert_eq!(
+        source.hash,
+        Some(ContentHash::keccak256(yaml.as_bytes()).to_string())
+    );
+    assert_eq!(
+        source.stored_hash,
+        ContentHash::keccak256(config.as_bytes()).to_string()
+    );
+
+    let origin = Origin {
+        sources: vec![source.clone()],
+        ..Origin::default()
+    };
+    let version = history
+        .create(std::slice::from_ref(config), "testnet1", origin)
+        .unwrap();
+    assert_eq!(history.meta(version).unwrap().sources, [source]);
+
+    // Sources recorded before the stored hash was
+    let legacy = serde_json::from_str::<ConfigSource>(
+        r#"{"url": null, "hash": "keccak256=0x00", "pinned": false}"#,
+    )
+    .unwrap();
+    assert_eq!(legacy.hash.as_deref(), Some("keccak256=0x00"));
+    assert!(legacy.stored_hash.is_empty());
+}
@@ -0,0 +1,67 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::inline_config::{self, MAX_CONFIG_SIZE};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::io::Write;
+
+const LIMIT: usize = 32 * 1024;
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn decodes_plain_and_gzipped_configs() {
+    let config = r#"{"chains": {}}"#;
+    assert_eq!(
+        inline_config::decode(config.as_bytes().to_vec(), LIMIT).unwrap(),
+        config
+    );
+    assert_eq!(
+        inline_config::decode(gzip(config.as_bytes()), LIMIT).unwrap(),
+        config
+    );
+}
+
+#[test]
+fn enforces_size_limits() {
+    let too_large = vec![b' '; LIMIT + 1];
+    assert!(inline_config::decode(too_large, LIMIT).is_err());
+
+    // Compresses to well under the limit
+    let exactly_max = gzip(&vec![b' '; MAX_CONFIG_SIZE]);
+    assert!(exactly_max.len() < LIMIT);
+    inline_config::decode(exactly_max, LIMIT).unwrap();
+
+    let bomb = gzip(&vec![b' '; MAX_CONFIG_SIZE + 1]);
+    assert!(bomb.len() < LIMIT);
+    assert!(inline_config::decode(bomb, LIMIT).is_err());
+}
+
+#[test]
+fn rejects_malformed_payloads() {
+    let mut truncated = gzip(br#"{"chains": {}}"#);
+    truncated.truncate(truncated.len() / 2);
+    assert!(inline_config::decode(truncated, LIMIT).is_err());
+
+    assert!(inline_config::decode(vec![0xff, 0xfe], LIMIT).is_err());
+}
+
+#[test]
+fn reads_up_to_the_limit() {
+    let contents = [1u8; 8];
+    assert!(inline_config::is_gzip(&gzip(&contents)));
+    assert!(!inline_config::is_gzip(&contents));
+
+    assert_eq!(
+        inline_config::read_limited(contents.as_slice(), 8).unwrap(),
+        Some(contents.to_vec())
+    );
+    assert_eq!(
+        inline_config::read_limited(contents.as_slice(), 7).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,82 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::ipfs;
+use cid::Cid;
+use cid::multihash::Multihash;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+const RAW: u64 = 0x55;
+const SHA2_256: u64 = 0x12;
+
+/// The CIDv1 of an empty raw block
+const EMPTY_RAW_CID: &str = "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku";
+
+fn sha256_multihash(bytes: &[u8]) -> Multihash<64> {
+    Multihash::wrap(SHA2_256, &Sha256::digest(bytes)).unwrap()
+}
+
+/// A single-block UnixFS file, as `ipfs add` would create it
+fn unixfs_file(contents: &[u8]) -> Vec<u8> {
+    assert!(
+        contents.len() < 128,
+        "lengths are encoded as single-byte varints"
+    );
+    let mut data = vec![0x08, 0x02, 0x12, contents.len() as u8];
+    data.extend_from_slice(contents);
+    data.extend_from_slice(&[0x18, contents.len() as u8]);
+
+    let mut node = vec![0x0a, data.len() as u8];
+    node.extend_from_slice(&data);
+    node
+}
+
+#[test]
+fn parses_ipfs_urls() {
+    let url = Url::parse(&format!("ipfs://{EMPTY_RAW_CID}")).unwrap();
+    let cid = ipfs::parse_url(&url).unwrap();
+    assert_eq!(cid.to_string(), EMPTY_RAW_CID);
+    ipfs::verify(&cid, Vec::new()).unwrap();
+
+    let gateway = Url::parse("http://127.0.0.1:8080").unwrap();
+    assert_eq!(
+        ipfs::gateway_url(&gateway, &cid).unwrap().as_str(),
+        format!("http://127.0.0.1:8080/ipfs/{EMPTY_RAW_CID}?format=raw")
+    );
+    let gateway = Url::parse("https://gateway.example.com/prefix/").unwrap();
+    assert_eq!(
+        ipfs::gateway_url(&gateway, &cid).unwrap().as_str(),
+        format!("https://gateway.example.com/prefix/ipfs/{EMPTY_RAW_CID}?format=raw")
+    );
+
+    for bad in [
+        format!("ipfs://{EMPTY_RAW_CID}/config.json"),
+        format!("ipfs://{EMPTY_RAW_CID}?format=car"),
+        "ipfs://not-a-cid".to_string(),
+        format!("https://{EMPTY_RAW_CID}"),
+    ] {
+        let url = Url::parse(&bad).unwrap();
+        assert!(ipfs::parse_url(&url).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn verifies_blocks_against_cids() {
+    let config = br#"{"chains": {}}"#;
+
+    let raw = Cid::new_v1(RAW, sha256_multihash(config));
+    assert_eq!(ipfs::verify(&raw, config.to_vec()).unwrap(), config);
+    assert!(ipfs::verify(&raw, br#"{"chains": []}"#.to_vec()).is_err());
+
+    // CIDv0, `ipfs add` without `--cid-version 1`
+    let node = unixfs_file(config);
+    let dag_pb = Cid::new_v0(sha256_multihash(&node)).unwrap();
+    assert_eq!(ipfs::verify(&dag_pb, node.clone()).unwrap(), config);
+    assert!(ipfs::verify(&dag_pb, config.to_vec()).is_err());
+
+    // A file split into multiple blocks
+    let mut linked = vec![0x12, 0x00];
+    linked.extend_from_slice(&node);
+    let linked_cid = Cid::new_v0(sha256_multihash(&linked)).unwrap();
+    assert!(ipfs::verify(&linked_cid, linked).is_err());
+}
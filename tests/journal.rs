@@ -0,0 +1,146 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::history::{ConfigHistory, Origin};
+use blueprint::journal::{ApplyError, ApplyIntent, Journal};
+use blueprint::state::StateStore;
+use blueprint_sdk as sdk;
+use color_eyre::eyre::eyre;
+use sdk::testing::tempfile;
+use sdk::tokio;
+
+#[test]
+fn records_applies_in_progress() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal = Journal::new(dir.path().join("apply.json"));
+    assert_eq!(journal.pending().unwrap(), None);
+
+    let intent = ApplyIntent {
+        version: 3,
+        active_version: Some(2),
+        previous_version: Some(1),
+        stopped: false,
+    };
+    journal.begin(&intent).unwrap();
+    assert_eq!(journal.pending().unwrap(), Some(intent));
+
+    journal.finish().unwrap();
+    assert_eq!(journal.pending().unwrap(), None);
+    // Finishing twice is harmless
+    journal.finish().unwrap();
+}
+
+#[test]
+fn rolls_back_interrupted_applies() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal = Journal::new(dir.path().join("apply.json"));
+    let state = StateStore::open(dir.path().join("state.json")).unwrap();
+
+    // Nothing to roll back
+    assert_eq!(journal.roll_back(&state).unwrap(), None);
+
+    state
+        .update(|state| {
+            state.activate(1);
+            state.activate(2);
+            state.stopped = true;
+        })
+        .unwrap();
+    let intent = ApplyIntent {
+        version: 3,
+        active_version: Some(2),
+        previous_version: Some(1),
+        stopped: true,
+    };
+    journal.begin(&intent).unwrap();
+
+    // The blueprint dies after switching to the new version
+    state
+        .update(|state| {
+            state.activate(3);
+            state.stopped = false;
+        })
+        .unwrap();
+
+    assert_eq!(journal.roll_back(&state).unwrap(), Some(intent));
+    let restored = state.get();
+    assert_eq!(restored.active_version, Some(2));
+    assert_eq!(restored.previous_version, Some(1));
+    assert!(restored.stopped);
+    assert_eq!(journal.pending().unwrap(), None);
+}
+
+#[test]
+fn ignores_partial_generations() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = ConfigHistory::new(dir.path().to_path_buf(), 10);
+    let version = history
+        .create(
+            &[String::from("{}")],
+            "testnet1,testnet2",
+            Origin::default(),
+        )
+        .unwrap();
+    assert_eq!(version, 1);
+
+    // A generation being staged when the blueprint died
+    let staging = dir.path().join(".staging");
+    std::fs::create_dir_all(staging.join("agent_configs")).unwrap();
+    std::fs::write(staging.join("agent_configs/0.json"), "{").unwrap();
+    // A generation without metadata, from an interrupted import
+    std::fs::create_dir_all(history.agent_configs_path(2)).unwrap();
+
+    assert_eq!(history.versions().unwrap(), [1]);
+    assert!(!history.contains(2));
+
+    history.clean_staging().unwrap();
+    assert!(!staging.exists());
+    // Cleaning without a staging directory is harmless
+    history.clean_staging().unwrap();
+
+    // The incomplete generation is replaced
+    let version = history
+        .create(
+            &[String::from("{}")],
+            "testnet1,testnet2",
+            Origin::default(),
+        )
+        .unwrap();
+    assert_eq!(version, 2);
+    assert_eq!(history.versions().unwrap(), [1, 2]);
+    assert!(!staging.exists());
+}
+
+#[tokio::test]
+async fn keeps_applies_that_failed_to_revert() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal = Journal::new(dir.path().join("apply.json"));
+    let intent = ApplyIntent {
+        version: 2,
+        active_version: Some(1),
+        previous_version: None,
+        stopped: false,
+    };
+
+    journal.run(&intent, async { Ok(()) }).await.unwrap();
+    assert_eq!(journal.pending().unwrap(), None);
+
+    // The previous version was restored, so there's nothing left to roll back
+    let reverted = journal
+        .run(&intent, async {
+            Err(ApplyError::Reverted(eyre!("relayer failed to start")))
+        })
+        .await
+        .unwrap_err();
+    assert!(reverted.to_string().contains("relayer failed to start"));
+    assert_eq!(journal.pending().unwrap(), None);
+
+    // The previous version didn't start either, so the next startup rolls back
+    let interrupted = journal
+        .run(&intent, async {
+            Err(ApplyError::Interrupted(eyre!("revert failed")))
+        })
+        .await
+        .unwrap_err();
+    assert!(format!("{interrupted:?}").contains("revert failed"));
+    assert_eq!(journal.pending().unwrap(), Some(intent));
+}
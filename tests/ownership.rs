@@ -0,0 +1,47 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::ownership::{BLUEPRINT_ID_LABEL, CONFIG_VERSION_LABEL, Owner, SERVICE_ID_LABEL};
+use std::collections::HashMap;
+
+#[test]
+fn owns_containers_with_its_labels() {
+    let owner = Owner {
+        blueprint_id: 7,
+        service_id: Some(3),
+    };
+
+    let mut labels = owner.labels();
+    assert_eq!(labels[BLUEPRINT_ID_LABEL], "7");
+    assert_eq!(labels[SERVICE_ID_LABEL], "3");
+    assert!(owner.owns(&labels));
+
+    // Extra labels don't matter
+    labels.insert(CONFIG_VERSION_LABEL.to_string(), String::from("1"));
+    assert!(owner.owns(&labels));
+
+    let other_service = Owner {
+        blueprint_id: 7,
+        service_id: Some(4),
+    };
+    assert!(!other_service.owns(&labels));
+    assert!(!owner.owns(&HashMap::new()));
+}
+
+#[test]
+fn owns_containers_without_a_service() {
+    let owner = Owner {
+        blueprint_id: 7,
+        service_id: None,
+    };
+
+    let labels = owner.labels();
+    assert_eq!(labels[SERVICE_ID_LABEL], "");
+    assert!(owner.owns(&labels));
+
+    let with_service = Owner {
+        blueprint_id: 7,
+        service_id: Some(0),
+    };
+    assert!(!with_service.owns(&labels));
+    assert!(!owner.owns(&with_service.labels()));
+}
@@ -0,0 +1,61 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::readiness::{ReadinessSettings, ReadySignal};
+
+const METRICS: &str = r#"# HELP hyperlane_contract_sync_block_height Index for a given chain
+# TYPE hyperlane_contract_sync_block_height gauge
+hyperlane_contract_sync_block_height{chain="testnet1",data_type="dispatched_messages"} 42
+hyperlane_contract_sync_stored_events{chain="testnet2",data_type="dispatched_messages"} 3
+"#;
+
+#[test]
+fn builds_signals_from_settings() {
+    let relay_chains = vec![String::from("testnet1"), String::from("testnet2")];
+    let settings = ReadinessSettings {
+        log_patterns: vec![String::from("Agent relayer starting up")],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        ReadySignal::from_settings(&settings, &relay_chains),
+        [
+            ReadySignal::MetricsEndpoint,
+            ReadySignal::ChainIndexing(String::from("testnet1")),
+            ReadySignal::ChainIndexing(String::from("testnet2")),
+            ReadySignal::LogLine(String::from("Agent relayer starting up")),
+        ]
+    );
+
+    let settings = ReadinessSettings {
+        require_chains_indexing: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        ReadySignal::from_settings(&settings, &relay_chains),
+        [ReadySignal::MetricsEndpoint]
+    );
+}
+
+#[test]
+fn matches_chain_indexing_metrics() {
+    let indexing = |chain: &str| ReadySignal::ChainIndexing(chain.to_string());
+
+    assert!(ReadySignal::MetricsEndpoint.is_satisfied(Some(""), ""));
+    assert!(!ReadySignal::MetricsEndpoint.is_satisfied(None, ""));
+
+    assert!(indexing("testnet1").is_satisfied(Some(METRICS), ""));
+    // Only the block height metric counts, not others with the chain's label
+    assert!(!indexing("testnet2").is_satisfied(Some(METRICS), ""));
+    // The label must match the whole chain name
+    assert!(!indexing("testnet").is_satisfied(Some(METRICS), ""));
+    assert!(!indexing("testnet1").is_satisfied(None, ""));
+}
+
+#[test]
+fn matches_log_patterns() {
+    let signal = ReadySignal::LogLine(String::from("Agent relayer starting up"));
+    let logs = "INFO hyperlane_base: Agent relayer starting up\nINFO indexing\n";
+
+    assert!(signal.is_satisfied(None, logs));
+    assert!(!signal.is_satisfied(Some(METRICS), "INFO indexing\n"));
+}
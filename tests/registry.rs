@@ -0,0 +1,296 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::agent_config;
+use blueprint::fetch::{ConfigFetcher, ConfigUrl, ContentHash, FetchSettings};
+use blueprint::registry::{
+    self,
+    layout::{self, RegistryChain},
+};
+use blueprint_sdk as sdk;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sdk::testing::tempfile;
+use sdk::tokio;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const TEST_ASSETS_PATH: &str = "./tests/assets";
+const CHAINS: [&str; 2] = ["testnet1", "testnet2"];
+
+/// The test registry's files, as `(path, contents)`
+fn registry_files() -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    for chain in CHAINS {
+        let metadata = std::fs::read_to_string(
+            Path::new(TEST_ASSETS_PATH).join(format!("{chain}-metadata.yaml.template")),
+        )
+        .unwrap();
+        let addresses = std::fs::read_to_string(
+            Path::new(TEST_ASSETS_PATH).join(format!("{chain}-addresses.yaml")),
+        )
+        .unwrap();
+        files.push((
+            format!("chains/{chain}/metadata.yaml"),
+            metadata.replace("{RPC_URL}", "127.0.0.1:8545"),
+        ));
+        files.push((format!("chains/{chain}/addresses.yaml"), addresses));
+    }
+    files
+}
+
+fn write_registry(dir: &Path) {
+    for (path, contents) in registry_files() {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+}
+
+/// A gzipped tarball of the test registry, under `prefix`
+fn registry_archive(prefix: &str) -> Vec<u8> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, contents) in registry_files() {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, format!("{prefix}{path}"), contents.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+fn chains() -> Vec<String> {
+    CHAINS.map(String::from).to_vec()
+}
+
+#[test]
+fn builds_agent_configs_from_registry_files() {
+    for prefix in ["", "hyperlane-registry-main/"] {
+        let registry = layout::read_archive(&registry_archive(prefix), &chains()).unwrap();
+        assert_eq!(registry.keys().collect::<Vec<_>>(), CHAINS);
+
+        let config = layout::build_agent_config(&chains(), &registry).unwrap();
+        let agent_config = agent_config::validate(std::slice::from_ref(&config)).unwrap();
+        agent_config.check_relay_chains(&chains()).unwrap();
+
+        let config = serde_json::from_str::<Value>(&config).unwrap();
+        let testnet1 = &config["chains"]["testnet1"];
+        assert_eq!(testnet1["domainId"], 31337);
+        assert_eq!(testnet1["rpcUrls"][0]["http"], "http://127.0.0.1:8545");
+        assert_eq!(
+            testnet1["mailbox"],
+            "0x0b48aF34f4c854F5ae1A3D587da471FeA45bAD52"
+        );
+        assert_eq!(
+            testnet1["interchainGasPaymaster"],
+            "0x0000000000000000000000000000000000000000"
+        );
+        assert_eq!(testnet1["index"]["from"], 10);
+    }
+
+    // Only the requested chains are read
+    let only = vec![String::from("testnet2")];
+    let registry = layout::read_archive(&registry_archive(""), &only).unwrap();
+    assert_eq!(registry.keys().collect::<Vec<_>>(), ["testnet2"]);
+}
+
+#[test]
+fn rejects_incomplete_registries() {
+    let registry = layout::read_archive(&registry_archive(""), &chains()).unwrap();
+
+    let missing = vec![String::from("testnet1"), String::from("testnet3")];
+    assert!(layout::build_agent_config(&missing, &registry).is_err());
+
+    let mut no_addresses = registry.clone();
+    no_addresses.get_mut("testnet1").unwrap().addresses = None;
+    assert!(layout::build_agent_config(&chains(), &no_addresses).is_err());
+
+    let mut renamed = registry;
+    let metadata = renamed
+        .get_mut("testnet1")
+        .unwrap()
+        .metadata
+        .as_mut()
+        .unwrap();
+    metadata["name"] = Value::from("testnet2");
+    assert!(layout::build_agent_config(&chains(), &renamed).is_err());
+
+    for bad in ["", "../etc", "Testnet1", "test/net"] {
+        assert!(
+            layout::chain_file(bad, layout::METADATA_FILE).is_err(),
+            "{bad}"
+        );
+    }
+
+    assert!(layout::read_archive(b"not a tarball", &chains()).is_err());
+    assert_eq!(
+        layout::read_archive(&registry_archive("a/b/"), &chains()).unwrap(),
+        BTreeMap::<String, RegistryChain>::new()
+    );
+}
+
+#[tokio::test]
+async fn fetches_registries() {
+    let root = tempfile::tempdir().unwrap();
+    let dir = root.path().join("registry");
+    write_registry(&dir);
+    let archive = registry_archive("");
+    let archive_path = root.path().join("registry.tar.gz");
+    std::fs::write(&archive_path, &archive).unwrap();
+
+    let fetcher = ConfigFetcher::new(
+        FetchSettings {
+            local_config_dir: Some(root.path().to_path_buf()),
+            ..FetchSettings::default()
+        },
+        false,
+    )
+    .unwrap();
+
+    let pin = ContentHash::sha256(&archive);
+    let urls = [
+        "registry+local:registry".to_string(),
+        "registry+local:registry/".to_string(),
+        format!("registry+file://{}", dir.display()),
+        "registry+local:registry.tar.gz".to_string(),
+        format!("registry+local:registry.tar.gz#{pin}"),
+    ]
+    .map(|url| url.parse::<ConfigUrl>().unwrap());
+    assert!(urls.iter().all(|url| url.registry));
+
+    let configs = fetcher.fetch_all(&urls, &chains()).await.unwrap();
+    for fetched in &configs {
+        assert_eq!(
+            serde_json::from_str::<Value>(&fetched.config).unwrap(),
+            serde_json::from_str::<Value>(&configs[0].config).unwrap()
+        );
+    }
+
+    // Only archives are downloaded as a whole, so only they have a hash
+    let hashes = configs
+        .iter()
+        .map(|fetched| fetched.hash)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hashes,
+        [
+            None,
+            None,
+            None,
+            Some(ContentHash::keccak256(&archive)),
+            Some(pin)
+        ]
+    );
+
+    // Registries need relay chains, and can't be fetched as configs
+    assert!(fetcher.fetch(&urls[0]).await.is_err());
+
+    let wrong_pin = format!(
+        "registry+local:registry.tar.gz#{}",
+        ContentHash::sha256(b"")
+    )
+    .parse::<ConfigUrl>()
+    .unwrap();
+    assert!(fetcher.fetch_all(&[wrong_pin], &chains()).await.is_err());
+
+    // Directories can't be pinned
+    let pinned_dir = format!("registry+local:registry#{pin}")
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert!(fetcher.fetch_all(&[pinned_dir], &chains()).await.is_err());
+
+    let outside = "registry+local:registry/../../etc"
+        .parse::<ConfigUrl>()
+        .unwrap();
+    assert!(fetcher.fetch_all(&[outside], &chains()).await.is_err());
+}
+
+#[test]
+fn default_chains_match_image() {
+    assert!(blueprint::IMAGE.ends_with(&format!(":{}", registry::SNAPSHOT_VERSION)));
+    // Loads, and checks the version of, the bundled snapshot
+    let ethereum = registry::default_chain("ethereum").unwrap();
+    assert_eq!(ethereum.domain_id, 1);
+    // The whole config is kept
+    assert_eq!(ethereum.config["domainId"], 1);
+}
+
+#[test]
+fn default_chains_are_full_configs() {
+    // Enough of the image's config to relay the chain without supplying any
+    let ethereum = &registry::default_chain("ethereum").unwrap().config;
+    assert!(ethereum.contains_key("mailbox"), "{ethereum:?}");
+    assert!(ethereum.contains_key("rpcUrls"), "{ethereum:?}");
+}
+
+#[test]
+fn builds_default_chain_snapshot() {
+    let mainnet = String::from(
+        r#"{"chains": {"ethereum": {"name": "ethereum", "domainId": 1, "protocol": "ethereum", "mailbox": "0x01"}}, "defaultRpcConsensusType": "fallback"}"#,
+    );
+    let testnet = String::from(
+        r#"{"chains": {"sepolia": {"name": "sepolia", "domainId": 11155111, "protocol": "ethereum"}}}"#,
+    );
+
+    let snapshot = registry::build_snapshot("agents-v0.0.1", &[mainnet.clone(), testnet]).unwrap();
+    let snapshot: Value = serde_json::from_str(&snapshot).unwrap();
+    assert_eq!(snapshot["version"], "agents-v0.0.1");
+    // Chains are kept whole, and nothing else is
+    assert_eq!(snapshot["chains"]["ethereum"]["mailbox"], "0x01");
+    assert_eq!(snapshot["chains"]["sepolia"]["domainId"], 11155111);
+    assert_eq!(snapshot.as_object().unwrap().len(), 2);
+
+    let duplicate = registry::build_snapshot("agents-v0.0.1", &[mainnet.clone(), mainnet]);
+    assert!(duplicate.is_err());
+
+    let no_protocol = String::from(r#"{"chains": {"ethereum": {"domainId": 1}}}"#);
+    assert!(registry::build_snapshot("agents-v0.0.1", &[no_protocol]).is_err());
+    let not_an_object = String::from(r#"{"chains": {"ethereum": 1}}"#);
+    assert!(registry::build_snapshot("agents-v0.0.1", &[not_an_object]).is_err());
+}
+
+/// Read one of the relayer image's default configs
+///
+/// From a checkout of the Hyperlane monorepo's `rust/main/config` at the image's tag if
+/// `HYPERLANE_AGENT_CONFIG_DIR` is set, otherwise from the image itself.
+fn image_config(file: &str) -> String {
+    if let Ok(dir) = std::env::var("HYPERLANE_AGENT_CONFIG_DIR") {
+        return std::fs::read_to_string(Path::new(&dir).join(file)).unwrap();
+    }
+
+    let output = std::process::Command::new("docker")
+        .args(["run", "--rm", "--entrypoint", "cat", blueprint::IMAGE])
+        .arg(format!("{}/{file}", registry::IMAGE_CONFIG_DIR))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Check the bundled default chains against the relayer image, or regenerate them with
+/// `UPDATE_DEFAULT_CHAINS=1`
+#[test]
+#[ignore = "needs Docker or HYPERLANE_AGENT_CONFIG_DIR"]
+fn default_chains_match_image_configs() {
+    let configs = registry::IMAGE_CONFIG_FILES.map(image_config);
+    let snapshot = registry::build_snapshot(registry::SNAPSHOT_VERSION, &configs).unwrap();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/registry/default_chains.json");
+    if std::env::var_os("UPDATE_DEFAULT_CHAINS").is_some() {
+        std::fs::write(path, snapshot).unwrap();
+        return;
+    }
+
+    let bundled: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let expected: Value = serde_json::from_str(&snapshot).unwrap();
+    assert!(
+        bundled == expected,
+        "The bundled default chains are out of date, regenerate them with `UPDATE_DEFAULT_CHAINS=1`"
+    );
+}
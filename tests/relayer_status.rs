@@ -0,0 +1,27 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::RunState;
+use blueprint::state::RelayerState;
+
+#[test]
+fn derives_run_state() {
+    let mut state = RelayerState::default();
+    assert_eq!(RunState::new(false, &state), RunState::NotStarted);
+
+    state.activate(1);
+    assert_eq!(RunState::new(true, &state), RunState::Running);
+    // Configured, but the container is gone or dead
+    assert_eq!(RunState::new(false, &state), RunState::Crashed);
+
+    state.stopped = true;
+    assert_eq!(RunState::new(false, &state), RunState::Stopped);
+    // What's actually running wins over the state
+    assert_eq!(RunState::new(true, &state), RunState::Running);
+
+    // Stopped before any configs were applied
+    let stopped = RelayerState {
+        stopped: true,
+        ..RelayerState::default()
+    };
+    assert_eq!(RunState::new(false, &stopped), RunState::Stopped);
+}
@@ -0,0 +1,207 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::agent_config;
+use blueprint::history::{ConfigHistory, Origin};
+use blueprint::rpc_urls::{self, RpcConsensusType};
+use blueprint::state::StateStore;
+use blueprint::store_rpc_urls;
+use blueprint_sdk as sdk;
+use sdk::testing::tempfile;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+const BASE: &str = r#"{"chains": {"ethereum": {"rpcUrls": [{"http": "https://old.example"}]}}}"#;
+
+/// A history with the base config applied as version 1, relaying `ethereum`
+fn applied(dir: &tempfile::TempDir) -> (ConfigHistory, StateStore) {
+    let history = ConfigHistory::new(dir.path().join("configs"), 10);
+    let state = StateStore::open(dir.path().join("state.json")).unwrap();
+    let version = history
+        .create(&[String::from(BASE)], "ethereum", Origin::default())
+        .unwrap();
+    state.update(|state| state.activate(version)).unwrap();
+    (history, state)
+}
+
+fn ethereum_rpc_urls(history: &ConfigHistory, version: u64) -> Vec<String> {
+    let configs = history
+        .config_files(version)
+        .unwrap()
+        .into_iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect::<Vec<_>>();
+    agent_config::validate(&configs).unwrap().chains["ethereum"]
+        .rpc_urls
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|url| url.http.clone())
+        .collect()
+}
+
+fn ethereum(url: &str) -> BTreeMap<String, Vec<String>> {
+    rpc_urls::parse_rpc_urls(&[format!("ethereum={url}")]).unwrap()
+}
+
+#[test]
+fn parses_rpc_urls() {
+    let entries = [
+        "ethereum=https://a.example",
+        " arbitrum = https://b.example ",
+        "ethereum=https://c.example",
+    ]
+    .map(String::from);
+    let rpc_urls = rpc_urls::parse_rpc_urls(&entries).unwrap();
+    assert_eq!(
+        rpc_urls["ethereum"],
+        ["https://a.example", "https://c.example"]
+    );
+    assert_eq!(rpc_urls["arbitrum"], ["https://b.example"]);
+
+    for bad in ["ethereum", "=https://a.example", "ethereum="] {
+        assert!(
+            rpc_urls::parse_rpc_urls(&[String::from(bad)]).is_err(),
+            "{bad}"
+        );
+    }
+
+    assert_eq!(
+        "quorum".parse::<RpcConsensusType>().unwrap(),
+        RpcConsensusType::Quorum
+    );
+    assert!("majority".parse::<RpcConsensusType>().is_err());
+}
+
+#[test]
+fn overlay_overrides_rpc_urls() {
+    let base = String::from(
+        r#"{"chains": {"ethereum": {"rpcUrls": [{"http": "https://old.example"}, {"http": "https://older.example"}]}}}"#,
+    );
+    let rpc_urls =
+        rpc_urls::parse_rpc_urls(&[String::from("ethereum=https://new.example")]).unwrap();
+    let overlay = rpc_urls::overlay(&rpc_urls, Some(RpcConsensusType::Fallback)).unwrap();
+
+    let config = agent_config::validate(&[base, overlay]).unwrap();
+    let urls = config.chains["ethereum"]
+        .rpc_urls
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|url| url.http.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(urls, ["https://new.example"]);
+    assert_eq!(
+        config.default_rpc_consensus_type.as_deref(),
+        Some("fallback")
+    );
+}
+
+#[test]
+fn rejects_bad_overrides() {
+    let rpc_urls = rpc_urls::parse_rpc_urls(&[String::from("ethereum=ws://rpc.example")]).unwrap();
+    let overlay = rpc_urls::overlay(&rpc_urls, None).unwrap();
+    let overlay: Value = serde_json::from_str(&overlay).unwrap();
+    assert_eq!(
+        overlay,
+        json!({ "chains": { "ethereum": { "rpcUrls": [{ "http": "ws://rpc.example" }] } } })
+    );
+    assert!(agent_config::validate(&[overlay.to_string()]).is_err());
+
+    let consensus = String::from(r#"{"defaultRpcConsensusType": "majority"}"#);
+    let err = agent_config::validate(&[consensus]).unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+}
+
+#[test]
+fn replaces_earlier_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let (history, state) = applied(&dir);
+
+    let first = store_rpc_urls(
+        &history,
+        &state,
+        &ethereum("https://a.example"),
+        None,
+        Origin::default(),
+    )
+    .unwrap();
+    assert_eq!(ethereum_rpc_urls(&history, first), ["https://a.example"]);
+    // Switching the running relayer over is left to the caller
+    assert_eq!(state.get().active_version, Some(1));
+    state.update(|state| state.activate(first)).unwrap();
+
+    let second = store_rpc_urls(
+        &history,
+        &state,
+        &ethereum("https://b.example"),
+        None,
+        Origin::default(),
+    )
+    .unwrap();
+    assert_eq!(ethereum_rpc_urls(&history, second), ["https://b.example"]);
+    assert_eq!(history.config_files(second).unwrap().len(), 2);
+    state.update(|state| state.activate(second)).unwrap();
+
+    // No overrides at all goes back to the configs as applied
+    let cleared =
+        store_rpc_urls(&history, &state, &BTreeMap::new(), None, Origin::default()).unwrap();
+    assert_eq!(
+        ethereum_rpc_urls(&history, cleared),
+        ["https://old.example"]
+    );
+    assert!(!history.rpc_urls_path(cleared).exists());
+
+    let err = store_rpc_urls(
+        &history,
+        &state,
+        &rpc_urls::parse_rpc_urls(&[String::from("arbitrum=https://a.example")]).unwrap(),
+        None,
+        Origin::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("arbitrum"), "{err}");
+}
+
+#[test]
+fn activates_overrides_of_a_stopped_relayer() {
+    let dir = tempfile::tempdir().unwrap();
+    let (history, state) = applied(&dir);
+    state.update(|state| state.stopped = true).unwrap();
+
+    let version = store_rpc_urls(
+        &history,
+        &state,
+        &ethereum("https://a.example"),
+        Some(RpcConsensusType::Quorum),
+        Origin::default(),
+    )
+    .unwrap();
+
+    // Stored and active for when the relayer is started again, but still stopped
+    let state = state.get();
+    assert_eq!(
+        (state.active_version, state.previous_version),
+        (Some(version), Some(1))
+    );
+    assert!(state.stopped);
+    assert!(!state.should_run());
+    assert_eq!(ethereum_rpc_urls(&history, version), ["https://a.example"]);
+}
+
+#[test]
+fn requires_applied_configs() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = ConfigHistory::new(dir.path().join("configs"), 10);
+    let state = StateStore::open(dir.path().join("state.json")).unwrap();
+
+    let err = store_rpc_urls(
+        &history,
+        &state,
+        &ethereum("https://a.example"),
+        None,
+        Origin::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("set_config"), "{err}");
+    assert!(history.versions().unwrap().is_empty());
+}
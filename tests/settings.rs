@@ -0,0 +1,55 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::settings::Settings;
+use std::time::Duration;
+
+fn set(name: &str, value: &str) {
+    // SAFETY: The environment is only touched by the single test in this binary
+    unsafe { std::env::set_var(name, value) };
+}
+
+fn unset(name: &str) {
+    // SAFETY: See `set`
+    unsafe { std::env::remove_var(name) };
+}
+
+// One test, since the environment is shared by every thread
+#[test]
+fn loads_timer_intervals() {
+    let intervals = [
+        (
+            "HYPERLANE_READINESS_INITIAL_INTERVAL_MS",
+            Duration::from_millis(7),
+        ),
+        (
+            "HYPERLANE_READINESS_MAX_INTERVAL_MS",
+            Duration::from_millis(7),
+        ),
+        ("HYPERLANE_GC_INTERVAL_SECS", Duration::from_secs(7)),
+        ("HYPERLANE_BALANCE_INTERVAL_SECS", Duration::from_secs(7)),
+    ];
+
+    for (name, _) in intervals {
+        set(name, "7");
+    }
+    let settings = Settings::from_env().unwrap();
+    let loaded = [
+        settings.readiness.initial_interval,
+        settings.readiness.max_interval,
+        settings.gc_interval,
+        settings.balances.interval,
+    ];
+    assert_eq!(loaded, intervals.map(|(_, interval)| interval));
+
+    // A zero interval would spin
+    for (name, _) in intervals {
+        set(name, "0");
+        let err = Settings::from_env().unwrap_err();
+        assert!(err.to_string().contains(name), "{err}");
+        set(name, "7");
+    }
+
+    for (name, _) in intervals {
+        unset(name);
+    }
+}
@@ -0,0 +1,166 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::agent_config::Protocol;
+use blueprint::signer::{self, ChainSigner, HexKey, RelayChain, Signers};
+use blueprint_sdk as sdk;
+use sdk::alloy::primitives::Address;
+use sdk::crypto::sp_core::{SpEcdsa, SpEd25519};
+use sdk::keystore::backends::Backend;
+use sdk::keystore::{Keystore, KeystoreConfig};
+use sdk::testing::tempfile;
+
+fn keystore() -> Keystore {
+    let keystore = Keystore::new(KeystoreConfig::new().in_memory(true)).unwrap();
+    keystore.generate::<SpEcdsa>(Some(&[7; 32])).unwrap();
+    keystore.generate::<SpEd25519>(Some(&[8; 32])).unwrap();
+    keystore
+}
+
+fn chain(name: &str, protocol: Protocol, bech32_prefix: Option<&str>) -> RelayChain {
+    RelayChain {
+        name: name.to_string(),
+        protocol,
+        bech32_prefix: bech32_prefix.map(String::from),
+    }
+}
+
+fn evm_chains() -> Vec<RelayChain> {
+    vec![
+        chain("testnet1", Protocol::Ethereum, None),
+        chain("testnet2", Protocol::Ethereum, None),
+    ]
+}
+
+fn evm_address(signers: &Signers, chain: &str) -> String {
+    match signers.for_chain(chain) {
+        Some(ChainSigner::Ethereum(key)) => key.address().unwrap().to_string(),
+        other => panic!("expected an EVM signer for `{chain}`, got {other:?}"),
+    }
+}
+
+#[test]
+fn derives_distinct_per_chain_keys() {
+    let signers = Signers::from_keystore(&keystore(), None, &evm_chains(), true).unwrap();
+
+    let default = signers.default.address().unwrap().to_string();
+    let testnet1 = evm_address(&signers, "testnet1");
+    let testnet2 = evm_address(&signers, "testnet2");
+    assert_ne!(default, testnet1);
+    assert_ne!(default, testnet2);
+    assert_ne!(testnet1, testnet2);
+
+    // Unknown chains fall back to the default signer
+    assert!(signers.for_chain("testnet3").is_none());
+
+    // Derivation is deterministic
+    let again = Signers::from_keystore(&keystore(), None, &evm_chains(), true).unwrap();
+    assert_eq!(evm_address(&again, "testnet1"), testnet1);
+}
+
+#[test]
+fn shares_default_key_without_per_chain_signers() {
+    let signers = Signers::from_keystore(&keystore(), None, &evm_chains(), false).unwrap();
+    assert!(signers.chains.is_empty());
+}
+
+#[test]
+fn signs_for_non_evm_chains() {
+    let chains = [
+        chain("solanamainnet", Protocol::Sealevel, None),
+        chain("neutron", Protocol::Cosmos, Some("neutron")),
+    ];
+    let signers = Signers::from_keystore(&keystore(), None, &chains, false).unwrap();
+
+    let Some(ChainSigner::Sealevel(sealevel)) = signers.for_chain("solanamainnet") else {
+        panic!("expected a Sealevel signer");
+    };
+    assert_eq!(sealevel.expose(), format!("0x{}", "08".repeat(32)));
+
+    let Some(ChainSigner::Cosmos { key, prefix }) = signers.for_chain("neutron") else {
+        panic!("expected a Cosmos signer");
+    };
+    assert_eq!(key.expose(), signers.default.expose());
+    assert_eq!(prefix, "neutron");
+}
+
+#[test]
+fn encodes_keys_as_prefixed_hex() {
+    let signers = Signers::from_keystore(&keystore(), None, &evm_chains(), true).unwrap();
+    for key in [&signers.default, signers.for_evm_chain("testnet1")] {
+        let bytes = key.wallet().unwrap().to_bytes();
+        assert_eq!(key.expose(), bytes.to_string());
+    }
+}
+
+#[test]
+fn rejects_unsignable_chains() {
+    let no_prefix = [chain("neutron", Protocol::Cosmos, None)];
+    assert!(Signers::from_keystore(&keystore(), None, &no_prefix, false).is_err());
+
+    let unsupported = [chain("starknet", Protocol::Starknet, None)];
+    assert!(Signers::from_keystore(&keystore(), None, &unsupported, false).is_err());
+
+    let no_ed25519_key = Keystore::new(KeystoreConfig::new().in_memory(true)).unwrap();
+    no_ed25519_key.generate::<SpEcdsa>(None).unwrap();
+    let sealevel = [chain("solanamainnet", Protocol::Sealevel, None)];
+    assert!(Signers::from_keystore(&no_ed25519_key, None, &sealevel, false).is_err());
+}
+
+#[test]
+fn writes_signer_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut chains = evm_chains();
+    chains.push(chain("solanamainnet", Protocol::Sealevel, None));
+    chains.push(chain("neutron", Protocol::Cosmos, Some("neutron")));
+    let signers = Signers::from_keystore(&keystore(), None, &chains, true).unwrap();
+
+    let file = signer::write_signer_config(dir.path(), &signers).unwrap();
+    let config: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(file.path()).unwrap()).unwrap();
+
+    assert_eq!(config["defaultSigner"]["type"], "hexKey");
+    assert_eq!(config["defaultSigner"]["key"], signers.default.expose());
+    for chain in &chains {
+        let signer = &config["chains"][&chain.name]["signer"];
+        assert_eq!(
+            signer["key"],
+            signers.for_chain(&chain.name).unwrap().key().expose()
+        );
+    }
+    assert_eq!(
+        config["chains"]["solanamainnet"]["signer"]["type"],
+        "hexKey"
+    );
+    assert_eq!(config["chains"]["neutron"]["signer"]["type"], "cosmosKey");
+    assert_eq!(config["chains"]["neutron"]["signer"]["prefix"], "neutron");
+
+    let path = file.path().to_path_buf();
+    drop(file);
+    assert!(!path.exists(), "the secret file should be removed on drop");
+}
+
+#[test]
+fn selects_keystore_keys() {
+    let keystore = keystore();
+    let first = HexKey::from_keystore(&keystore, None).unwrap();
+
+    let generated = HexKey::generate(&keystore).unwrap();
+    let address = generated.address().unwrap();
+    assert_ne!(address, first.address().unwrap());
+
+    let selected = HexKey::from_keystore(&keystore, Some(address)).unwrap();
+    assert_eq!(selected.expose(), generated.expose());
+    assert!(HexKey::from_keystore(&keystore, Some(Address::ZERO)).is_err());
+
+    // Per-chain keys are derived from the selected key
+    let signers = Signers::from_keystore(&keystore, Some(address), &evm_chains(), true).unwrap();
+    assert_eq!(signers.default.expose(), generated.expose());
+    assert_eq!(
+        signers.for_evm_chain("testnet1").expose(),
+        generated.derive("testnet1").unwrap().expose()
+    );
+    assert_eq!(
+        signers.for_evm_chain("testnet3").expose(),
+        generated.expose()
+    );
+}
@@ -0,0 +1,97 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::state::StateStore;
+use blueprint_sdk as sdk;
+use sdk::testing::tempfile;
+
+#[test]
+fn persists_state_across_reopens() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    let store = StateStore::open(&path).unwrap();
+    assert_eq!(store.get().active_version, None);
+    store
+        .update(|state| {
+            state.activate(3);
+            state.stopped = true;
+        })
+        .unwrap();
+
+    let reopened = StateStore::open(&path).unwrap().get();
+    assert_eq!(reopened.active_version, Some(3));
+    assert!(reopened.stopped);
+    assert!(!path.with_extension("tmp").exists());
+}
+
+#[test]
+fn fills_in_missing_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    std::fs::write(&path, "{}").unwrap();
+    assert_eq!(StateStore::open(&path).unwrap().get().active_version, None);
+
+    std::fs::write(&path, r#"{"active_version": 2, "stopped": true}"#).unwrap();
+    let state = StateStore::open(&path).unwrap().get();
+    assert_eq!(state.active_version, Some(2));
+    assert!(state.stopped);
+}
+
+#[test]
+fn rejects_corrupt_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    // A write cut short
+    std::fs::write(&path, r#"{"active_version": 2, "sto"#).unwrap();
+    let err = StateStore::open(&path).unwrap_err();
+    assert!(err.to_string().contains("is corrupt"));
+}
+
+#[test]
+fn ignores_interrupted_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    let store = StateStore::open(&path).unwrap();
+    store.update(|state| state.activate(1)).unwrap();
+
+    // The temporary file of a write that never got renamed into place
+    std::fs::write(path.with_extension("tmp"), "{").unwrap();
+    assert_eq!(
+        StateStore::open(&path).unwrap().get().active_version,
+        Some(1)
+    );
+}
+
+#[test]
+fn stopped_relayer_stays_stopped_across_restarts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    let store = StateStore::open(&path).unwrap();
+    assert!(!store.get().should_run());
+    store
+        .update(|state| {
+            state.activate(1);
+            state.set_container(String::from("relayer"));
+        })
+        .unwrap();
+    assert!(StateStore::open(&path).unwrap().get().should_run());
+
+    // As `stop_relayer` leaves it, whether or not the container was removed
+    store.update(|state| state.stopped = true).unwrap();
+    let restarted = StateStore::open(&path).unwrap().get();
+    assert!(!restarted.should_run());
+    assert_eq!(restarted.container_id.as_deref(), Some("relayer"));
+
+    // Until configs are applied again
+    store
+        .update(|state| {
+            state.activate(2);
+            state.stopped = false;
+        })
+        .unwrap();
+    assert!(StateStore::open(&path).unwrap().get().should_run());
+}
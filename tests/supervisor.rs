@@ -0,0 +1,21 @@
+use hyperlane_relayer_blueprint_lib as blueprint;
+
+use blueprint::supervisor::SupervisorSettings;
+use std::time::Duration;
+
+#[test]
+fn backoff_doubles_up_to_the_limit() {
+    let settings = SupervisorSettings {
+        backoff_initial: Duration::from_secs(1),
+        backoff_max: Duration::from_secs(10),
+        ..Default::default()
+    };
+
+    let delays = (1..=6)
+        .map(|attempt| settings.backoff(attempt).as_secs())
+        .collect::<Vec<_>>();
+    assert_eq!(delays, [1, 2, 4, 8, 10, 10]);
+
+    // Large attempt counts saturate instead of overflowing
+    assert_eq!(settings.backoff(u32::MAX), Duration::from_secs(10));
+}